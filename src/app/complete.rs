@@ -0,0 +1,116 @@
+use std::path::Path;
+
+/// Tab completion of the directory being typed at the end of the new-session prompt.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// The prompt with the directory extended as far as the matches agree.
+    pub line: String,
+    /// Names still matching when more than one does.
+    pub candidates: Vec<String>,
+}
+
+/// Complete the last word of `line`, `[DIR...] [-- COMMAND]`, against the
+/// directories on disk, resolving relative paths against `cwd`. Only directories
+/// are offered since sessions need one. None when nothing matches or the word
+/// belongs to the command.
+pub fn complete_dir(line: &str, cwd: &Path) -> Option<Completion> {
+    let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let (head, word) = line.split_at(start);
+    if head.split_whitespace().any(|arg| arg == "--") || word.starts_with('-') {
+        return None;
+    }
+    let path = ["git:", "mkdir:"]
+        .iter()
+        .find_map(|prefix| word.strip_prefix(prefix))
+        .unwrap_or(word);
+    let (dir, partial) = match path.rfind('/') {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
+    };
+    let base = cwd.join(shellexpand::tilde(dir).as_ref());
+    let mut names: Vec<String> = std::fs::read_dir(&base)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(partial))
+        // Hidden directories only once the word asks for them
+        .filter(|name| !name.starts_with('.') || partial.starts_with('.'))
+        .filter(|name| base.join(name).is_dir())
+        .collect();
+    names.sort();
+    let keep = &line[..line.len() - partial.len()];
+    match names.as_slice() {
+        [] => None,
+        [name] => Some(Completion {
+            line: format!("{keep}{name}/"),
+            candidates: Vec::new(),
+        }),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, name| {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(common.len().min(name.len()), |((i, _), _)| i);
+                &common[..len]
+            });
+            Some(Completion {
+                line: format!("{keep}{common}"),
+                candidates: names.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn tree(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("murmur-complete-{name}-{}", std::process::id()));
+        for dir in ["api", "app", "apps/web", ".hidden"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("apple.txt"), "").unwrap();
+        root
+    }
+
+    #[test]
+    fn completes_a_single_match_with_a_slash() {
+        let root = tree("single");
+        let completion = complete_dir("apps/w", &root).unwrap();
+        assert_eq!(completion.line, "apps/web/");
+        assert!(completion.candidates.is_empty());
+        let completion = complete_dir("git:apps/", &root).unwrap();
+        assert_eq!(completion.line, "git:apps/web/");
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn extends_to_the_common_prefix_and_lists_directories() {
+        let root = tree("common");
+        let completion = complete_dir("web a", &root).unwrap();
+        assert_eq!(completion.line, "web ap");
+        assert_eq!(completion.candidates, ["api", "app", "apps"]);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn hides_dot_directories_until_asked() {
+        let root = tree("hidden");
+        assert_eq!(complete_dir(".h", &root).unwrap().line, ".hidden/");
+        let all = complete_dir("", &root).unwrap();
+        assert!(!all.candidates.contains(&".hidden".to_string()));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn leaves_the_command_alone() {
+        let root = tree("command");
+        assert_eq!(complete_dir("api -- a", &root), None);
+        assert_eq!(complete_dir("zzz", &root), None);
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,201 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// A cell on the copy-mode view, 0-indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub row: u16,
+    pub col: u16,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CopyOutcome {
+    Moved,
+    Yank,
+    Exit,
+}
+
+/// Copy mode: a frozen snapshot of the session's view with a movable cursor
+/// and an optional selection, which runs like text from the anchor to the cursor.
+pub struct CopyMode {
+    pub screen: vt100::Screen,
+    pub cursor: Pos,
+    pub anchor: Option<Pos>,
+}
+
+impl CopyMode {
+    /// Start at the program's cursor on a snapshot of `screen`.
+    pub fn new(screen: &vt100::Screen) -> Self {
+        let (row, col) = screen.cursor_position();
+        Self {
+            screen: screen.clone(),
+            cursor: Pos { row, col },
+            anchor: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent) -> CopyOutcome {
+        let (rows, cols) = self.screen.size();
+        let cursor = &mut self.cursor;
+        match key.code {
+            KeyCode::Char('h') | KeyCode::Left => cursor.col = cursor.col.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => cursor.col = (cursor.col + 1).min(cols - 1),
+            KeyCode::Char('k') | KeyCode::Up => cursor.row = cursor.row.saturating_sub(1),
+            KeyCode::Char('j') | KeyCode::Down => cursor.row = (cursor.row + 1).min(rows - 1),
+            KeyCode::Char('0') | KeyCode::Home => cursor.col = 0,
+            KeyCode::Char('$') | KeyCode::End => cursor.col = line_end(&self.screen, cursor.row),
+            KeyCode::Char('v') => {
+                self.anchor = match self.anchor {
+                    Some(_) => None,
+                    None => Some(*cursor),
+                };
+            }
+            KeyCode::Char('y') | KeyCode::Enter => return CopyOutcome::Yank,
+            KeyCode::Esc | KeyCode::Char('q') => return CopyOutcome::Exit,
+            _ => {}
+        }
+        CopyOutcome::Moved
+    }
+
+    /// Selection bounds, start first, both inclusive.
+    pub fn selection(&self) -> Option<(Pos, Pos)> {
+        let anchor = self.anchor?;
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Columns selected on `row`, as an inclusive range.
+    pub fn selected_cols(&self, row: u16) -> Option<(u16, u16)> {
+        let (start, end) = self.selection()?;
+        if row < start.row || row > end.row {
+            return None;
+        }
+        let (_, cols) = self.screen.size();
+        let first = if row == start.row { start.col } else { 0 };
+        let last = if row == end.row { end.col } else { cols - 1 };
+        Some((first, last))
+    }
+
+    /// The selected text. Trailing blanks are dropped from each row, and rows
+    /// the program wrapped are joined without a newline.
+    pub fn selected_text(&self) -> String {
+        let Some((start, end)) = self.selection() else {
+            return String::new();
+        };
+        let mut text = String::new();
+        for row in start.row..=end.row {
+            let Some((first, last)) = self.selected_cols(row) else {
+                continue;
+            };
+            let mut line = String::new();
+            for col in first..=last {
+                match self.screen.cell(row, col) {
+                    Some(cell) if cell.is_wide_continuation() => {}
+                    Some(cell) if cell.has_contents() => line.push_str(cell.contents()),
+                    _ => line.push(' '),
+                }
+            }
+            let wrapped = row < end.row && self.screen.row_wrapped(row);
+            if wrapped {
+                text.push_str(&line);
+            } else {
+                text.push_str(line.trim_end());
+                if row < end.row {
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+}
+
+/// Last column with text on `row`.
+fn line_end(screen: &vt100::Screen, row: u16) -> u16 {
+    let (_, cols) = screen.size();
+    (0..cols)
+        .rev()
+        .find(|&col| {
+            screen
+                .cell(row, col)
+                .is_some_and(|cell| cell.has_contents())
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn copy_mode(rows: u16, cols: u16, output: &[u8]) -> CopyMode {
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(output);
+        let mut copy = CopyMode::new(parser.screen());
+        copy.cursor = Pos { row: 0, col: 0 };
+        copy
+    }
+
+    fn press(copy: &mut CopyMode, keys: &str) -> CopyOutcome {
+        let mut outcome = CopyOutcome::Moved;
+        for c in keys.chars() {
+            outcome = copy.handle_key(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        outcome
+    }
+
+    #[test]
+    fn cursor_stays_on_screen() {
+        let mut copy = copy_mode(3, 10, b"");
+        press(&mut copy, "hk");
+        assert_eq!(copy.cursor, Pos { row: 0, col: 0 });
+        press(&mut copy, "jjjjlllllllllllll");
+        assert_eq!(copy.cursor, Pos { row: 2, col: 9 });
+    }
+
+    #[test]
+    fn yank_within_a_line() {
+        let mut copy = copy_mode(3, 20, b"hello world");
+        press(&mut copy, "llllllvllll");
+        assert_eq!(press(&mut copy, "y"), CopyOutcome::Yank);
+        assert_eq!(copy.selected_text(), "world");
+    }
+
+    #[test]
+    fn yank_across_lines_trims_trailing_blanks() {
+        let mut copy = copy_mode(3, 20, b"first\r\nsecond line");
+        press(&mut copy, "lvj");
+        copy.cursor.col = 5;
+        assert_eq!(copy.selected_text(), "irst\nsecond");
+    }
+
+    #[test]
+    fn yank_joins_wrapped_rows() {
+        let mut copy = copy_mode(3, 5, b"abcdefgh");
+        press(&mut copy, "vj$");
+        assert_eq!(copy.selected_text(), "abcdefgh");
+    }
+
+    #[test]
+    fn yank_skips_wide_continuations() {
+        let mut copy = copy_mode(2, 10, "\u{4F60}\u{597D}!".as_bytes());
+        press(&mut copy, "vllll");
+        assert_eq!(copy.selected_text(), "\u{4F60}\u{597D}!");
+    }
+
+    #[test]
+    fn v_toggles_selection() {
+        let mut copy = copy_mode(2, 10, b"abc");
+        press(&mut copy, "v");
+        assert!(copy.selection().is_some());
+        press(&mut copy, "v");
+        assert!(copy.selection().is_none());
+        assert_eq!(copy.selected_text(), "");
+    }
+
+    #[test]
+    fn selection_runs_backwards() {
+        let mut copy = copy_mode(2, 10, b"abcdef");
+        copy.cursor.col = 4;
+        press(&mut copy, "vhhh");
+        assert_eq!(copy.selected_cols(0), Some((1, 4)));
+        assert_eq!(copy.selected_text(), "bcde");
+    }
+}
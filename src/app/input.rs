@@ -0,0 +1,191 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+/// What a line being typed into the hint bar is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    Note,
+    Rename,
+    /// `[DIR...] [-- COMMAND [ARGS...]]` for a new session.
+    NewSession,
+    /// Part of a session's name or window title to switch to.
+    FindSession,
+    /// Yes/no question before closing the focused session. The buffer holds the
+    /// question; `y` confirms and any other key cancels.
+    Close,
+    /// Yes/no question before quitting while programs are still running.
+    Quit,
+    /// Text to find in the session's output; Tab toggles case sensitivity.
+    Search {
+        case_sensitive: bool,
+    },
+}
+
+impl InputKind {
+    fn label(self) -> &'static str {
+        match self {
+            InputKind::Note => "note",
+            InputKind::Rename => "rename",
+            InputKind::NewSession => "new",
+            InputKind::FindSession => "find session",
+            InputKind::Close => "close",
+            InputKind::Quit => "quit",
+            InputKind::Search {
+                case_sensitive: false,
+            } => "search",
+            InputKind::Search {
+                case_sensitive: true,
+            } => "search (case)",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InputOutcome {
+    Editing,
+    Submit,
+    Cancel,
+}
+
+/// Single-line text input shown in place of the hint bar.
+#[derive(Debug)]
+pub struct LineInput {
+    pub kind: InputKind,
+    pub buffer: String,
+    /// Shown after the text until the next key, e.g. completion candidates.
+    pub hint: Option<String>,
+}
+
+impl LineInput {
+    pub fn new(kind: InputKind, initial: &str) -> Self {
+        Self {
+            kind,
+            buffer: initial.to_string(),
+            hint: None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.kind.label()
+    }
+
+    /// 1-indexed column just past the typed text, where the cursor sits.
+    pub fn cursor_col(&self) -> u16 {
+        // leading space + label + ": "
+        (self.label().width() + self.buffer.width() + 4) as u16
+    }
+
+    pub fn handle_key(&mut self, key: &KeyEvent) -> InputOutcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        self.hint = None;
+        match key.code {
+            KeyCode::Enter => InputOutcome::Submit,
+            KeyCode::Esc => InputOutcome::Cancel,
+            KeyCode::Char('c') if ctrl => InputOutcome::Cancel,
+            KeyCode::Char('u') if ctrl => {
+                self.buffer.clear();
+                InputOutcome::Editing
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.buffer.push(c);
+                InputOutcome::Editing
+            }
+            KeyCode::Backspace => {
+                self.buffer.pop();
+                InputOutcome::Editing
+            }
+            KeyCode::Tab => {
+                if let InputKind::Search { case_sensitive } = &mut self.kind {
+                    *case_sensitive = !*case_sensitive;
+                }
+                InputOutcome::Editing
+            }
+            _ => InputOutcome::Editing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn type_str(input: &mut LineInput, s: &str) {
+        for c in s.chars() {
+            input.handle_key(&key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn typing_and_backspace() {
+        let mut input = LineInput::new(InputKind::Note, "");
+        type_str(&mut input, "abc");
+        input.handle_key(&key(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(input.buffer, "ab");
+    }
+
+    #[test]
+    fn starts_with_initial_text() {
+        let mut input = LineInput::new(InputKind::Note, "old");
+        type_str(&mut input, "!");
+        assert_eq!(input.buffer, "old!");
+    }
+
+    #[test]
+    fn ctrl_u_clears() {
+        let mut input = LineInput::new(InputKind::Note, "old");
+        let outcome = input.handle_key(&key(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(outcome, InputOutcome::Editing);
+        assert!(input.buffer.is_empty());
+    }
+
+    #[test]
+    fn enter_submits_and_escape_cancels() {
+        let mut input = LineInput::new(InputKind::Note, "");
+        assert_eq!(
+            input.handle_key(&key(KeyCode::Enter, KeyModifiers::NONE)),
+            InputOutcome::Submit
+        );
+        assert_eq!(
+            input.handle_key(&key(KeyCode::Esc, KeyModifiers::NONE)),
+            InputOutcome::Cancel
+        );
+        assert_eq!(
+            input.handle_key(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            InputOutcome::Cancel
+        );
+    }
+
+    #[test]
+    fn tab_toggles_search_case() {
+        let mut input = LineInput::new(
+            InputKind::Search {
+                case_sensitive: false,
+            },
+            "",
+        );
+        input.handle_key(&key(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(
+            input.kind,
+            InputKind::Search {
+                case_sensitive: true
+            }
+        );
+        assert_eq!(input.label(), "search (case)");
+
+        let mut note = LineInput::new(InputKind::Note, "");
+        note.handle_key(&key(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(note.kind, InputKind::Note);
+    }
+
+    #[test]
+    fn cursor_col_follows_text() {
+        let mut input = LineInput::new(InputKind::Note, "");
+        assert_eq!(input.cursor_col(), 8); // " note: " then cursor
+        type_str(&mut input, "\u{4F60}");
+        assert_eq!(input.cursor_col(), 10);
+    }
+}
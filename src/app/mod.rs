@@ -1,40 +1,379 @@
-use std::path::PathBuf;
+pub mod complete;
+pub mod copy;
+pub mod input;
+pub mod search;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use crossterm::event::{self, Event};
 
-use crate::layout::focus_bar_rows;
-use crate::session::Session;
+use crate::cli::Args;
+use crate::config::Config;
+use crate::control::Control;
+use crate::hook;
+use crate::layout::{self, focus_bar_rows, BarContent};
+use crate::session::{self, Session, SessionSpec};
+use copy::CopyMode;
+use input::LineInput;
+use search::Search;
 
 pub struct App {
     pub sessions: Vec<Session>,
     pub should_quit: bool,
     pub prefix_armed: bool,
+    /// Text being typed into the hint bar, if any.
+    pub input: Option<LineInput>,
+    /// Copy mode over the focused session, if active.
+    pub copy: Option<CopyMode>,
+    /// Search results being stepped through in the focused session.
+    pub search: Option<Search>,
+    /// Text last copied in copy mode, pasted with Ctrl+\ P.
+    pub paste_buffer: Option<String>,
+    /// Typed input goes to every running session, not just the focused one.
+    pub broadcast: bool,
+    /// Listener for commands from other programs, while `control_socket` is on.
+    pub control: Option<Control>,
+    /// Two sessions shown side by side, if split.
+    pub split: Option<Split>,
     pub bar_rows: u16,
     pub rows: u16,
     pub cols: u16,
-    pub focus_idx: usize,
+    /// Id of the focused session. Resolved to an index with `focus_idx()`, so
+    /// focus follows the session when others are added, closed or reordered.
+    pub focus_id: u64,
     pub update_available: Option<String>,
+    /// The focused session's window title and runtime labels as of the last
+    /// tick, to notice hint bar changes that come without output.
+    pub last_hint: (String, String, Option<String>),
+    /// Shown in the hint bar until the next key press.
+    pub error_message: Option<String>,
+    pub config: Config,
+    /// Command-line options, applied again when the config is reloaded.
+    pub args: Args,
+    /// Overlay mode: whether the bars are currently drawn over program output.
+    pub overlay_shown: bool,
+    pub last_output: Instant,
+    /// Loop iterations since startup, for animation phases.
+    pub frame: u64,
+}
+
+/// Split view: two sessions side by side, by id. Input goes to the focused one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    pub left: u64,
+    pub right: u64,
 }
 
 impl App {
-    pub fn new(cwd: PathBuf, rows: u16, cols: u16) -> Result<Self> {
-        let bar_rows = focus_bar_rows("", false);
-        let term_rows = rows.saturating_sub(bar_rows);
-        let session = Session::spawn(cwd, term_rows, cols)?;
+    /// Spawn one session per spec. Specs that fail to spawn are reported through
+    /// `error_message`; it is an error only if none of them start.
+    pub fn new(specs: &[SessionSpec], rows: u16, cols: u16, config: Config) -> Result<Self> {
+        let content = BarContent {
+            pinned_prompt: "",
+            is_ai_tool: false,
+            has_note: false,
+            cols,
+        };
+        let bar_rows = focus_bar_rows(&content, &config.bar);
+        let term_rows = layout::term_rows(rows, bar_rows, config.focus_render);
+
+        let mut sessions = Vec::new();
+        let mut errors = Vec::new();
+        let mut last_err = None;
+        for spec in specs {
+            match Session::spawn_spec(spec, term_rows, cols) {
+                Ok(session) => sessions.push(
+                    session
+                        .with_scrollback(config.scrollback)
+                        .with_ai_tools(&config.ai_tools),
+                ),
+                Err(e) => {
+                    errors.push(format!("{}: {e}", spec.cwd.display()));
+                    last_err = Some(e);
+                }
+            }
+        }
+        if sessions.is_empty() {
+            return Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no sessions to start")));
+        }
 
-        Ok(Self {
-            sessions: vec![session],
+        let mut app = Self {
+            sessions,
             should_quit: false,
             prefix_armed: false,
+            input: None,
+            copy: None,
+            search: None,
+            paste_buffer: None,
+            broadcast: false,
+            control: None,
+            split: None,
             bar_rows,
             rows,
             cols,
-            focus_idx: 0,
+            focus_id: 0,
             update_available: None,
+            last_hint: Default::default(),
+            error_message: (!errors.is_empty()).then(|| errors.join("; ")),
+            config,
+            args: Args::default(),
+            overlay_shown: false,
+            last_output: Instant::now(),
+            frame: 0,
+        };
+        app.focus(0);
+        app.refresh_session_names();
+        Ok(app)
+    }
+
+    pub fn index_of_id(&self, id: u64) -> Option<usize> {
+        self.sessions.iter().position(|s| s.id == id)
+    }
+
+    /// Indexes of the split's left and right sessions, while both exist.
+    pub fn split_indices(&self) -> Option<(usize, usize)> {
+        let split = self.split?;
+        Some((
+            self.index_of_id(split.left)?,
+            self.index_of_id(split.right)?,
+        ))
+    }
+
+    /// Focus the session at `idx`, clamped to the last session so a stale index
+    /// never lands on a missing one. Returns false when there is nothing to focus.
+    pub fn focus(&mut self, idx: usize) -> bool {
+        let Some(idx) = clamp_index(idx, self.sessions.len()) else {
+            return false;
+        };
+        self.focus_id = self.sessions[idx].id;
+        self.sessions[idx].mark_seen();
+        true
+    }
+
+    /// Index of the session after (or before) the focused one, wrapping around.
+    pub fn neighbor_idx(&self, forward: bool) -> usize {
+        cycle_index(self.focus_idx(), self.sessions.len(), forward)
+    }
+
+    /// Swap the focused session with the one after (or before) it. Focus moves
+    /// with the session; returns false at either end of the list.
+    pub fn move_focused(&mut self, forward: bool) -> bool {
+        let idx = self.focus_idx();
+        let other = if forward {
+            idx + 1
+        } else {
+            match idx.checked_sub(1) {
+                Some(other) => other,
+                None => return false,
+            }
+        };
+        if other >= self.sessions.len() {
+            return false;
+        }
+        self.sessions.swap(idx, other);
+        true
+    }
+
+    /// Index of the first session after the focused one whose name or window title
+    /// contains `query`, ignoring case. Wraps around, ending at the focused session,
+    /// so repeating a query steps through every match.
+    pub fn find_session(&self, query: &str) -> Option<usize> {
+        let query = query.to_lowercase();
+        let len = self.sessions.len();
+        let start = self.focus_idx();
+        (1..=len).map(|step| (start + step) % len).find(|&i| {
+            let session = &self.sessions[i];
+            session.name.to_lowercase().contains(&query)
+                || session.window_title().to_lowercase().contains(&query)
         })
     }
 
+    /// Index of the focused session, falling back to the first one.
+    pub fn focus_idx(&self) -> usize {
+        self.index_of_id(self.focus_id).unwrap_or(0)
+    }
+
+    /// Name each session after its directory, adding parent directories where
+    /// basenames collide. Sessions the user renamed keep their name.
+    pub fn refresh_session_names(&mut self) {
+        let paths: Vec<&Path> = self
+            .sessions
+            .iter()
+            .map(|s| Path::new(s.project_path()))
+            .collect();
+        let names = session::display_names(&paths);
+        for (session, name) in self.sessions.iter_mut().zip(names) {
+            if !session.custom_name {
+                session.name = name;
+            }
+        }
+    }
+
+    /// Spawn another session sized for the current layout and return its index.
+    pub fn add_session(&mut self, spec: &SessionSpec) -> Result<usize> {
+        let term_rows = layout::term_rows(self.rows, self.bar_rows, self.config.focus_render);
+        let session = Session::spawn_spec(spec, term_rows, self.cols)?
+            .with_scrollback(self.config.scrollback)
+            .with_ai_tools(&self.config.ai_tools);
+        self.sessions.push(session);
+        self.refresh_session_names();
+        Ok(self.sessions.len() - 1)
+    }
+
+    /// Terminate and remove the session at `idx`. Focus stays put unless it was on the
+    /// closed session, which hands it to the next one, and a split showing it
+    /// ends. Returns false when no sessions are left.
+    pub fn close_session(&mut self, idx: usize) -> bool {
+        if idx >= self.sessions.len() {
+            return !self.sessions.is_empty();
+        }
+        let session = self.sessions.remove(idx);
+        let id = session.id;
+        session.terminate(Duration::from_millis(self.config.close_grace_ms));
+        if self.split.is_some_and(|s| s.left == id || s.right == id) {
+            self.split = None;
+        }
+        if self.focus_id == id {
+            self.focus(idx);
+        }
+        self.refresh_session_names();
+        !self.sessions.is_empty()
+    }
+
+    /// Send input with `write` to the session at `idx`, and while broadcasting to every
+    /// other running session too. Their write errors are reported rather than returned,
+    /// so one closed PTY doesn't stop the rest.
+    pub fn send_input(
+        &mut self,
+        idx: usize,
+        mut write: impl FnMut(&mut Session) -> Result<()>,
+    ) -> Result<()> {
+        let mut errors = Vec::new();
+        for (i, session) in self.sessions.iter_mut().enumerate() {
+            if i == idx {
+                write(session)?;
+            } else if self.broadcast && session.status() == session::SessionStatus::Running {
+                if let Err(e) = write(session) {
+                    errors.push(format!("{}: {e}", session.name));
+                }
+            }
+        }
+        for message in errors {
+            self.push_error(message);
+        }
+        Ok(())
+    }
+
+    /// Index of the session a control request names: its name, or its 1-based
+    /// position as on the number keys.
+    pub fn control_target(&self, session: &str) -> Option<usize> {
+        self.sessions
+            .iter()
+            .position(|s| s.name == session)
+            .or_else(|| {
+                let n: usize = session.parse().ok()?;
+                (1..=self.sessions.len()).contains(&n).then(|| n - 1)
+            })
+    }
+
+    /// Question to ask before quitting, while `confirm_quit` is on and any
+    /// session's program is still running.
+    pub fn quit_question(&self) -> Option<String> {
+        let running = self
+            .sessions
+            .iter()
+            .filter(|s| s.status() == session::SessionStatus::Running)
+            .count();
+        match running {
+            _ if !self.config.confirm_quit => None,
+            0 => None,
+            1 => Some("1 session running. Quit anyway? [y/N]".into()),
+            n => Some(format!("{n} sessions running. Quit anyway? [y/N]")),
+        }
+    }
+
+    /// Switch to a reloaded config without touching the running sessions, which
+    /// pick up its AI tools. `focus_render` keeps its startup value, and
+    /// `scrollback` applies to sessions opened from now on.
+    pub fn apply_config(&mut self, mut config: Config) {
+        self.args.apply_display_flags(&mut config);
+        config.focus_render = self.config.focus_render;
+        for session in &mut self.sessions {
+            session.set_ai_tools(&config.ai_tools);
+        }
+        self.config = config;
+    }
+
+    /// Directory a new session starts in: where the focused session currently is,
+    /// or `$HOME` when that directory is gone.
+    pub fn new_session_dir(&self) -> PathBuf {
+        self.sessions
+            .get(self.focus_idx())
+            .map(|s| s.current_dir())
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(crate::config::home_dir)
+    }
+
+    /// Give a session a custom name; an empty name goes back to the directory name.
+    pub fn rename_session(&mut self, idx: usize, name: &str) {
+        let Some(session) = self.sessions.get_mut(idx) else {
+            return;
+        };
+        session.custom_name = !name.is_empty();
+        if session.custom_name {
+            session.name = name.to_string();
+        }
+        self.refresh_session_names();
+    }
+
+    /// Notice sessions whose program has exited and run the on-exit hook for each.
+    /// Returns true when any session exited since the last call.
+    pub fn poll_exits(&mut self) -> bool {
+        let mut errors = Vec::new();
+        let mut exited = false;
+        for session in &mut self.sessions {
+            let Some(code) = session.poll_exit() else {
+                continue;
+            };
+            exited = true;
+            if let Some(command) = &self.config.hooks.on_exit {
+                if let Err(e) = hook::spawn_exit_hook(command, session, code) {
+                    errors.push(format!("on_exit hook: {e}"));
+                }
+            }
+        }
+        for message in errors {
+            self.push_error(message);
+        }
+        exited
+    }
+
+    /// Whether the session at `idx` shows a different window title, uptime or
+    /// quiet time than at the last call. Programs can retitle themselves and then
+    /// go quiet, and the clocks move on without any output to redraw for.
+    pub fn hint_changed(&mut self, idx: usize) -> bool {
+        let Some(session) = self.sessions.get(idx) else {
+            return false;
+        };
+        let (uptime, quiet) = session.runtime_labels();
+        let hint = (session.window_title(), uptime, quiet);
+        if hint == self.last_hint {
+            return false;
+        }
+        self.last_hint = hint;
+        true
+    }
+
+    /// Record a message for the hint bar, appending to any message not yet seen.
+    pub fn push_error(&mut self, message: String) {
+        self.error_message = Some(match self.error_message.take() {
+            Some(prev) => format!("{prev}; {message}"),
+            None => message,
+        });
+    }
+
     pub fn poll_event(timeout: std::time::Duration) -> Result<Option<Event>> {
         if event::poll(timeout)? {
             Ok(Some(event::read()?))
@@ -43,3 +382,246 @@ impl App {
         }
     }
 }
+
+fn clamp_index(idx: usize, len: usize) -> Option<usize> {
+    len.checked_sub(1).map(|last| idx.min(last))
+}
+
+fn cycle_index(idx: usize, len: usize, forward: bool) -> usize {
+    match len {
+        0 => 0,
+        _ if forward => (idx + 1) % len,
+        _ => (idx + len - 1) % len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn clamp_index_in_range() {
+        assert_eq!(clamp_index(1, 3), Some(1));
+    }
+
+    #[test]
+    fn clamp_index_past_end_after_delete() {
+        // The last of three sessions was focused, then deleted
+        assert_eq!(clamp_index(2, 2), Some(1));
+    }
+
+    #[test]
+    fn clamp_index_no_sessions() {
+        assert_eq!(clamp_index(0, 0), None);
+    }
+
+    #[test]
+    fn cycle_index_wraps() {
+        assert_eq!(cycle_index(0, 3, true), 1);
+        assert_eq!(cycle_index(2, 3, true), 0);
+        assert_eq!(cycle_index(0, 3, false), 2);
+        assert_eq!(cycle_index(0, 1, true), 0);
+        assert_eq!(cycle_index(0, 0, false), 0);
+    }
+
+    fn cat_spec(cwd: &Path) -> SessionSpec {
+        SessionSpec {
+            cwd: cwd.to_path_buf(),
+            command: vec!["cat".into()],
+            name: None,
+            env: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn rename_session_survives_refresh_and_clears() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        let derived = app.sessions[0].name.clone();
+
+        app.rename_session(0, "api");
+        app.refresh_session_names();
+        assert_eq!(app.sessions[0].name, "api");
+
+        app.rename_session(0, "");
+        assert!(!app.sessions[0].custom_name);
+        assert_eq!(app.sessions[0].name, derived);
+    }
+
+    #[test]
+    fn split_indices_follow_ids() {
+        let dir = std::env::temp_dir();
+        let mut app = App::new(&[cat_spec(&dir)], 24, 80, Config::default()).unwrap();
+        app.add_session(&cat_spec(&dir)).unwrap();
+        assert_eq!(app.split_indices(), None);
+        app.split = Some(Split {
+            left: app.sessions[1].id,
+            right: app.sessions[0].id,
+        });
+        assert_eq!(app.split_indices(), Some((1, 0)));
+        app.sessions.remove(0);
+        assert_eq!(app.split_indices(), None);
+    }
+
+    #[test]
+    fn move_focused_swaps_and_keeps_focus() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        let ids: Vec<u64> = app.sessions.iter().map(|s| s.id).collect();
+        assert!(!app.move_focused(false));
+        assert!(app.move_focused(true));
+        assert_eq!(app.focus_idx(), 1);
+        assert!(app.move_focused(true));
+        assert!(!app.move_focused(true));
+        let order: Vec<u64> = app.sessions.iter().map(|s| s.id).collect();
+        assert_eq!(order, [ids[1], ids[2], ids[0]]);
+        assert_eq!(app.focus_id, ids[0]);
+    }
+
+    #[test]
+    fn find_session_steps_through_matches() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        app.rename_session(0, "api-server");
+        app.rename_session(1, "web");
+        app.rename_session(2, "API docs");
+        assert_eq!(app.find_session("api"), Some(2));
+        app.focus(2);
+        assert_eq!(app.find_session("api"), Some(0));
+        assert_eq!(app.find_session("WEB"), Some(1));
+        assert_eq!(app.find_session("docs"), Some(2));
+        assert_eq!(app.find_session("nope"), None);
+    }
+
+    #[test]
+    fn close_session_moves_focus_and_ends_split() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        let ids: Vec<u64> = app.sessions.iter().map(|s| s.id).collect();
+        app.focus(2);
+        app.split = Some(Split {
+            left: ids[2],
+            right: ids[0],
+        });
+        assert!(app.close_session(2));
+        assert_eq!(app.focus_id, ids[1]);
+        assert_eq!(app.split, None);
+        assert!(app.close_session(0));
+        assert_eq!(app.focus_id, ids[1]);
+        assert!(!app.close_session(0));
+        assert!(app.sessions.is_empty());
+    }
+
+    #[test]
+    fn send_input_reaches_every_session_while_broadcasting() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        let mut sent = Vec::new();
+        app.send_input(1, |s| {
+            sent.push(s.id);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(sent, [app.sessions[1].id]);
+
+        app.broadcast = true;
+        sent.clear();
+        app.send_input(1, |s| {
+            sent.push(s.id);
+            Ok(())
+        })
+        .unwrap();
+        let ids: Vec<u64> = app.sessions.iter().map(|s| s.id).collect();
+        assert_eq!(sent, ids);
+
+        // Only the focused session's failure stops the caller
+        let focused = ids[1];
+        app.send_input(1, |s| match s.id == focused {
+            true => Ok(()),
+            false => Err(anyhow::anyhow!("closed")),
+        })
+        .unwrap();
+        assert!(app.error_message.unwrap().contains("closed"));
+    }
+
+    #[test]
+    fn control_target_by_name_or_position() {
+        let dir = std::env::temp_dir();
+        let mut app =
+            App::new(&[cat_spec(&dir), cat_spec(&dir)], 24, 80, Config::default()).unwrap();
+        app.rename_session(1, "web");
+        assert_eq!(app.control_target("web"), Some(1));
+        assert_eq!(app.control_target("1"), Some(0));
+        assert_eq!(app.control_target("3"), None);
+        assert_eq!(app.control_target("0"), None);
+        assert_eq!(app.control_target("api"), None);
+    }
+
+    #[test]
+    fn quit_question_counts_running_sessions() {
+        let dir = std::env::temp_dir();
+        let specs = [cat_spec(&dir), cat_spec(&dir)];
+        let mut app = App::new(&specs, 24, 80, Config::default()).unwrap();
+        assert_eq!(
+            app.quit_question().as_deref(),
+            Some("2 sessions running. Quit anyway? [y/N]")
+        );
+        app.close_session(0);
+        assert_eq!(
+            app.quit_question().as_deref(),
+            Some("1 session running. Quit anyway? [y/N]")
+        );
+        app.config.confirm_quit = false;
+        assert_eq!(app.quit_question(), None);
+    }
+
+    #[test]
+    fn apply_config_keeps_sessions_and_render_mode() {
+        let dir = std::env::temp_dir();
+        let mut app = App::new(&[cat_spec(&dir)], 24, 80, Config::default()).unwrap();
+        let id = app.sessions[0].id;
+        app.args.no_color = true;
+        let mut config = Config::default();
+        config.focus_render = crate::config::FocusRender::Overlay;
+        config.theme.pin_text = "1;33".into();
+        config.ai_tools.clear();
+        app.apply_config(config);
+        assert_eq!(app.sessions[0].id, id);
+        assert_eq!(
+            app.config.focus_render,
+            crate::config::FocusRender::ScrollRegion
+        );
+        assert_eq!(app.config.theme.pin_text, "1;33");
+        assert!(!app.config.theme.color);
+    }
+
+    #[test]
+    fn hint_changed_reports_each_change_once() {
+        let dir = std::env::temp_dir();
+        let mut app = App::new(&[cat_spec(&dir)], 24, 80, Config::default()).unwrap();
+        assert!(app.hint_changed(0));
+        assert!(!app.hint_changed(0));
+        let chunks = [b"\x1b]0;build\x07".to_vec()];
+        app.sessions[0].ingest_output(&chunks, true).unwrap();
+        assert!(app.hint_changed(0));
+        assert!(!app.hint_changed(0));
+        assert_eq!(app.last_hint.0, "build");
+    }
+
+    #[test]
+    fn add_session_appends_and_names() {
+        let dir = std::env::temp_dir();
+        let mut app = App::new(&[cat_spec(&dir)], 24, 80, Config::default()).unwrap();
+        let idx = app.add_session(&cat_spec(&dir)).unwrap();
+        assert_eq!(idx, 1);
+        assert_eq!(app.sessions.len(), 2);
+        assert_eq!(app.focus_idx(), 0);
+        assert!(app.new_session_dir().is_dir());
+    }
+}
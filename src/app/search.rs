@@ -0,0 +1,131 @@
+use unicode_width::UnicodeWidthStr;
+
+/// A match in a session's output. `line` counts from the oldest scrollback
+/// row; `col` and `width` are in terminal columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub line: usize,
+    pub col: u16,
+    pub width: u16,
+}
+
+/// Every occurrence of `query` in `lines`, newest first.
+pub fn find_matches(lines: &[String], query: &str, case_sensitive: bool) -> Vec<Match> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let fold = |s: &str| {
+        if case_sensitive {
+            s.to_string()
+        } else {
+            s.to_lowercase()
+        }
+    };
+    let needle = fold(query);
+    let width = needle.width() as u16;
+    let mut matches = Vec::new();
+    for (line, text) in lines.iter().enumerate().rev() {
+        let haystack = fold(text);
+        let found: Vec<_> = haystack
+            .match_indices(&needle)
+            .map(|(i, _)| Match {
+                line,
+                col: haystack[..i].width() as u16,
+                width,
+            })
+            .collect();
+        matches.extend(found.into_iter().rev());
+    }
+    matches
+}
+
+/// An active search: its matches and which one is shown.
+pub struct Search {
+    pub matches: Vec<Match>,
+    pub current: usize,
+}
+
+impl Search {
+    pub fn new(matches: Vec<Match>) -> Self {
+        Self {
+            matches,
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> Option<Match> {
+        self.matches.get(self.current).copied()
+    }
+
+    /// Move to the next older match, wrapping to the newest.
+    pub fn next(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+    }
+
+    /// Move to the next newer match, wrapping to the oldest.
+    pub fn prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_newest_first() {
+        let found = find_matches(
+            &lines(&["error one", "ok", "error two error"]),
+            "error",
+            false,
+        );
+        let at: Vec<_> = found.iter().map(|m| (m.line, m.col)).collect();
+        assert_eq!(at, vec![(2, 10), (2, 0), (0, 0)]);
+        assert!(found.iter().all(|m| m.width == 5));
+    }
+
+    #[test]
+    fn case_insensitive_by_default() {
+        let text = lines(&["Error", "error"]);
+        assert_eq!(find_matches(&text, "ERROR", false).len(), 2);
+        assert_eq!(find_matches(&text, "Error", true).len(), 1);
+    }
+
+    #[test]
+    fn columns_count_wide_characters() {
+        let found = find_matches(&lines(&["\u{4F60}\u{597D} hi"]), "hi", false);
+        assert_eq!(found[0].col, 5);
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        assert!(find_matches(&lines(&["abc"]), "", false).is_empty());
+    }
+
+    #[test]
+    fn next_and_prev_wrap() {
+        let text = lines(&["a", "a", "a"]);
+        let mut search = Search::new(find_matches(&text, "a", false));
+        assert_eq!(search.current().unwrap().line, 2);
+        search.prev();
+        assert_eq!(search.current().unwrap().line, 0);
+        search.next();
+        search.next();
+        assert_eq!(search.current().unwrap().line, 1);
+    }
+
+    #[test]
+    fn no_matches_has_no_current() {
+        let mut search = Search::new(Vec::new());
+        search.next();
+        assert!(search.current().is_none());
+    }
+}
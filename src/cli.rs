@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+
+use crate::config::{Config, StartupSession};
+use crate::session::SessionSpec;
+use crate::ui::theme::Glyphs;
+
+/// Command-line options: `murmur [--ascii] [--no-color] [DIR...] [-- COMMAND [ARGS...]]`.
+#[derive(Debug, Default, Clone)]
+pub struct Args {
+    /// Draw every decorative glyph in ASCII.
+    pub ascii: bool,
+    /// Draw the bars without colors.
+    pub no_color: bool,
+    /// One session is opened per directory.
+    pub paths: Vec<String>,
+    /// Command run in each session instead of `$SHELL`.
+    pub command: Vec<String>,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        for arg in args.by_ref() {
+            match arg.as_str() {
+                "--" => break,
+                "--ascii" => parsed.ascii = true,
+                "--no-color" => parsed.no_color = true,
+                _ if arg.starts_with("--") => bail!("unknown argument: {arg}"),
+                _ => parsed.paths.push(arg),
+            }
+        }
+        parsed.command = args.collect();
+        Ok(parsed)
+    }
+
+    /// Apply `--ascii` and `--no-color` (or `NO_COLOR`) on top of the config file.
+    pub fn apply_display_flags(&self, config: &mut Config) {
+        if self.ascii {
+            config.glyphs = Glyphs::ascii();
+            for tool in &mut config.ai_tools {
+                if !tool.glyph.is_ascii() {
+                    tool.glyph = "*".to_string();
+                }
+            }
+        }
+        if self.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            config.theme.color = false;
+        }
+    }
+
+    /// Resolve the requested directories against `cwd`. Invalid directories are
+    /// skipped and reported in the returned messages. Without any paths a single
+    /// session opens in `cwd`.
+    pub fn session_specs(&self, cwd: &Path) -> (Vec<SessionSpec>, Vec<String>) {
+        if self.paths.is_empty() {
+            let spec = SessionSpec {
+                cwd: cwd.to_path_buf(),
+                command: self.command.clone(),
+                name: None,
+                env: HashMap::new(),
+            };
+            return (vec![spec], Vec::new());
+        }
+
+        let mut specs = Vec::new();
+        let mut skipped = Vec::new();
+        for raw in &self.paths {
+            match prepare_dir(raw, cwd) {
+                Ok(dir) => specs.push(SessionSpec {
+                    cwd: dir,
+                    command: self.command.clone(),
+                    name: None,
+                    env: HashMap::new(),
+                }),
+                Err(message) => skipped.push(message),
+            }
+        }
+        (specs, skipped)
+    }
+}
+
+/// Specs for the config's `[[sessions]]`, with the same directory rules as the
+/// command line.
+pub fn startup_specs(sessions: &[StartupSession], cwd: &Path) -> (Vec<SessionSpec>, Vec<String>) {
+    let mut specs = Vec::new();
+    let mut skipped = Vec::new();
+    for session in sessions {
+        match prepare_dir(&session.path, cwd) {
+            Ok(dir) => specs.push(SessionSpec {
+                cwd: dir,
+                command: session.command.clone(),
+                name: session.name.clone(),
+                env: session.env.clone(),
+            }),
+            Err(message) => skipped.push(message),
+        }
+    }
+    (specs, skipped)
+}
+
+/// The directory for a session at `raw`, or the message to skip it with. A
+/// `mkdir:` prefix creates a missing directory first; without it nothing is created.
+fn prepare_dir(raw: &str, cwd: &Path) -> Result<PathBuf, String> {
+    let path = match raw.strip_prefix("mkdir:") {
+        Some(rest) => {
+            let expanded = shellexpand::tilde(rest);
+            std::fs::create_dir_all(cwd.join(expanded.as_ref()))
+                .map_err(|e| format!("mkdir {rest}: {e}"))?;
+            rest
+        }
+        None => raw,
+    };
+    resolve_dir(path, cwd).ok_or_else(|| format!("not a directory: {raw}"))
+}
+
+/// `raw` with `~` expanded, made absolute against `cwd`, if it is a directory.
+/// A `git:` prefix moves up to the root of the repository or worktree holding it,
+/// when there is one.
+fn resolve_dir(raw: &str, cwd: &Path) -> Option<PathBuf> {
+    let (raw, git) = match raw.strip_prefix("git:") {
+        Some(rest) => (rest, true),
+        None => (raw, false),
+    };
+    let expanded = shellexpand::tilde(raw);
+    let dir = std::fs::canonicalize(cwd.join(expanded.as_ref()))
+        .ok()
+        .filter(|dir| dir.is_dir())?;
+    match git {
+        true => Some(repo_root(&dir).unwrap_or(dir)),
+        false => Some(dir),
+    }
+}
+
+/// The nearest directory at or above `path` with a `.git` entry, which is a file
+/// in a worktree.
+fn repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Parse the new-session prompt, `[[git:|mkdir:]DIR...] [-- COMMAND [ARGS...]]`, with the
+/// same rules as the command line. Paths are resolved against `cwd`.
+pub fn prompt_specs(line: &str, cwd: &Path) -> Result<(Vec<SessionSpec>, Vec<String>)> {
+    let args = Args::parse(line.split_whitespace().map(str::to_string))?;
+    Ok(args.session_specs(cwd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parse_empty() {
+        let args = parse(&[]).unwrap();
+        assert!(!args.ascii);
+        assert!(args.paths.is_empty());
+        assert!(args.command.is_empty());
+    }
+
+    #[test]
+    fn parse_ascii() {
+        assert!(parse(&["--ascii"]).unwrap().ascii);
+    }
+
+    #[test]
+    fn parse_no_color() {
+        assert!(parse(&["--no-color"]).unwrap().no_color);
+        assert!(!parse(&[]).unwrap().no_color);
+    }
+
+    #[test]
+    fn parse_unknown_is_error() {
+        assert!(parse(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn parse_paths_and_command() {
+        let args = parse(&["~/a", "--ascii", "b", "--", "claude", "--resume"]).unwrap();
+        assert!(args.ascii);
+        assert_eq!(args.paths, vec!["~/a", "b"]);
+        assert_eq!(args.command, vec!["claude", "--resume"]);
+    }
+
+    #[test]
+    fn parse_flags_after_separator_belong_to_command() {
+        let args = parse(&["--", "--ascii"]).unwrap();
+        assert!(!args.ascii);
+        assert_eq!(args.command, vec!["--ascii"]);
+    }
+
+    #[test]
+    fn specs_default_to_cwd() {
+        let args = parse(&["--", "htop"]).unwrap();
+        let (specs, skipped) = args.session_specs(Path::new("/tmp"));
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].cwd, Path::new("/tmp"));
+        assert_eq!(specs[0].command, vec!["htop"]);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn startup_specs_carry_name_and_env() {
+        let cwd = std::env::current_dir().unwrap();
+        let sessions = [
+            StartupSession {
+                path: "src".into(),
+                name: Some("code".into()),
+                command: vec!["claude".into()],
+                env: HashMap::from([("A".into(), "1".into())]),
+            },
+            StartupSession {
+                path: "no-such-dir".into(),
+                name: None,
+                command: Vec::new(),
+                env: HashMap::new(),
+            },
+        ];
+        let (specs, skipped) = startup_specs(&sessions, &cwd);
+        assert_eq!(specs.len(), 1);
+        assert!(specs[0].cwd.ends_with("src"));
+        assert_eq!(specs[0].name.as_deref(), Some("code"));
+        assert_eq!(specs[0].env["A"], "1");
+        assert_eq!(skipped, ["not a directory: no-such-dir"]);
+    }
+
+    #[test]
+    fn specs_skip_invalid_directories() {
+        let cwd = std::env::current_dir().unwrap();
+        let args = parse(&["src", "no-such-dir", "Cargo.toml"]).unwrap();
+        let (specs, skipped) = args.session_specs(&cwd);
+        assert_eq!(specs.len(), 1);
+        assert!(specs[0].cwd.ends_with("src"));
+        assert!(specs[0].cwd.is_absolute());
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped[0].contains("no-such-dir"));
+    }
+
+    #[test]
+    fn prompt_specs_path_and_command() {
+        let cwd = std::env::current_dir().unwrap();
+        let (specs, skipped) = prompt_specs("src -- claude --resume", &cwd).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(specs.len(), 1);
+        assert!(specs[0].cwd.ends_with("src"));
+        assert_eq!(specs[0].command, vec!["claude", "--resume"]);
+    }
+
+    #[test]
+    fn git_prefix_opens_the_repo_root() {
+        let root = std::env::temp_dir().join(format!("murmur-git-{}", std::process::id()));
+        let sub = root.join("repo/src/deep");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::create_dir_all(root.join("repo/.git")).unwrap();
+        std::fs::create_dir_all(root.join("plain")).unwrap();
+
+        let (specs, skipped) = prompt_specs("git:repo/src/deep repo/src git:plain", &root).unwrap();
+        assert!(skipped.is_empty());
+        let repo = std::fs::canonicalize(root.join("repo")).unwrap();
+        assert_eq!(specs[0].cwd, repo);
+        assert!(specs[1].cwd.ends_with("src"));
+        // Outside a repo the path is used as given
+        assert!(specs[2].cwd.ends_with("plain"));
+
+        let (specs, skipped) = prompt_specs("git:nope", &root).unwrap();
+        assert!(specs.is_empty());
+        assert_eq!(skipped, ["not a directory: git:nope"]);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn mkdir_prefix_creates_the_directory() {
+        let root = std::env::temp_dir().join(format!("murmur-mkdir-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("file"), "").unwrap();
+
+        let (specs, skipped) = prompt_specs("new/deep", &root).unwrap();
+        assert!(specs.is_empty());
+        assert_eq!(skipped, ["not a directory: new/deep"]);
+        assert!(!root.join("new").exists());
+
+        let (specs, skipped) = prompt_specs("mkdir:new/deep mkdir:file/sub", &root).unwrap();
+        assert_eq!(specs.len(), 1);
+        assert!(specs[0].cwd.ends_with("new/deep"));
+        assert!(root.join("new/deep").is_dir());
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].starts_with("mkdir file/sub: "));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn prompt_specs_empty_uses_cwd_and_shell() {
+        let (specs, _) = prompt_specs("  ", Path::new("/tmp")).unwrap();
+        assert_eq!(specs[0].cwd, Path::new("/tmp"));
+        assert!(specs[0].command.is_empty());
+    }
+}
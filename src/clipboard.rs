@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+
+use crate::config::ClipboardBackend;
+use crate::ui::ansi;
+
+/// Resolve `Auto`: OSC 52 over SSH, where no local clipboard tool can reach
+/// the user's desktop, and the system clipboard otherwise.
+fn resolve(backend: ClipboardBackend, ssh: bool) -> ClipboardBackend {
+    match backend {
+        ClipboardBackend::Auto if ssh => ClipboardBackend::Osc52,
+        ClipboardBackend::Auto => ClipboardBackend::System,
+        other => other,
+    }
+}
+
+/// The system clipboard tool: `pbcopy` on macOS, `wl-copy` under Wayland,
+/// `xclip` otherwise. Each reads the text on stdin.
+fn system_command(wayland: bool) -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("pbcopy")
+    } else if wayland {
+        Command::new("wl-copy")
+    } else {
+        let mut cmd = Command::new("xclip");
+        cmd.args(["-selection", "clipboard"]);
+        cmd
+    }
+}
+
+/// Feed `text` to `cmd` on a background thread so a slow tool never blocks the UI.
+fn pipe_to(mut cmd: Command, text: &str) -> Result<()> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take();
+    let text = text.to_string();
+    std::thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        drop(stdin);
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Put `text` on the clipboard. OSC 52 is written to `w`, the user's terminal.
+pub fn copy(w: &mut impl Write, backend: ClipboardBackend, text: &str) -> Result<()> {
+    let ssh = std::env::var_os("SSH_TTY").is_some();
+    match resolve(backend, ssh) {
+        ClipboardBackend::Osc52 => {
+            ansi::osc52_copy(w, text);
+            Ok(())
+        }
+        _ => {
+            let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+            pipe_to(system_command(wayland), text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_uses_osc52_over_ssh() {
+        assert_eq!(
+            resolve(ClipboardBackend::Auto, true),
+            ClipboardBackend::Osc52
+        );
+        assert_eq!(
+            resolve(ClipboardBackend::Auto, false),
+            ClipboardBackend::System
+        );
+        assert_eq!(
+            resolve(ClipboardBackend::System, true),
+            ClipboardBackend::System
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn system_command_by_display() {
+        assert_eq!(system_command(true).get_program(), "wl-copy");
+        let cmd = system_command(false);
+        assert_eq!(cmd.get_program(), "xclip");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["-selection", "clipboard"]
+        );
+    }
+
+    #[test]
+    fn osc52_backend_writes_to_terminal() {
+        let mut buf = Vec::new();
+        copy(&mut buf, ClipboardBackend::Osc52, "hi").unwrap();
+        assert_eq!(buf, b"\x1b]52;c;aGk=\x07");
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::key::keymap::Keymap;
+use crate::session::{default_ai_tools, AiTool};
+use crate::ui::theme::{Glyphs, Theme};
+
+/// User configuration loaded from `~/.config/murmur/config.toml`.
+/// Every field has a default, so a missing file or section keeps today's behavior.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub focus_render: FocusRender,
+    /// Rows of output kept per session for scrolling back with Ctrl+\ PageUp.
+    pub scrollback: usize,
+    /// Capture the mouse so the wheel scrolls back. Off leaves clicks and
+    /// selection to the terminal.
+    pub mouse: bool,
+    /// How long a closed session's program gets to exit after SIGHUP before it
+    /// is killed.
+    pub close_grace_ms: u64,
+    /// Ask before quitting while programs are still running.
+    pub confirm_quit: bool,
+    /// Save the open sessions on quit and reopen them on the next launch
+    /// without directories or a command. Saved to `~/.murmur/state.toml`.
+    pub restore_sessions: bool,
+    /// Accept commands for sessions on `~/.murmur/control.sock`.
+    pub control_socket: bool,
+    /// Shell to exec into after quitting, started in the focused session's directory.
+    pub exec_on_quit: Option<String>,
+    pub bar: BarConfig,
+    pub theme: Theme,
+    pub glyphs: Glyphs,
+    pub detect: DetectConfig,
+    pub keys: KeysConfig,
+    /// Keys for the prefix commands, by action name.
+    pub keymap: Keymap,
+    pub hooks: HooksConfig,
+    pub notify: NotifyConfig,
+    pub clipboard: ClipboardConfig,
+    /// AI tools to recognize; a configured list replaces the defaults.
+    pub ai_tools: Vec<AiTool>,
+    /// Sessions opened when murmur starts without directories or a command.
+    pub sessions: Vec<StartupSession>,
+    /// Prefix + key sends the text; keys are single characters. Built-in
+    /// bindings take precedence. See `key::macro_bytes` for escapes.
+    pub macros: HashMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            focus_render: FocusRender::default(),
+            scrollback: 10_000,
+            mouse: true,
+            close_grace_ms: 2000,
+            confirm_quit: true,
+            restore_sessions: false,
+            control_socket: false,
+            exec_on_quit: None,
+            bar: BarConfig::default(),
+            theme: Theme::default(),
+            glyphs: Glyphs::default(),
+            detect: DetectConfig::default(),
+            keys: KeysConfig::default(),
+            keymap: Keymap::default(),
+            hooks: HooksConfig::default(),
+            notify: NotifyConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            ai_tools: default_ai_tools(),
+            sessions: Vec::new(),
+            macros: HashMap::new(),
+        }
+    }
+}
+
+/// How Focus mode keeps the bars apart from program output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FocusRender {
+    /// Shrink the program with a DECSTBM scroll region and keep the bars below it.
+    #[default]
+    ScrollRegion,
+    /// Run the program at full height and draw the bars over it while output is idle.
+    /// For terminals that mishandle scroll regions.
+    Overlay,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BarConfig {
+    /// Draw a horizontal rule between program output and the bars.
+    pub separator: bool,
+    /// Most rows a multi-line pinned prompt may take; the rest is summarized.
+    pub max_pin_rows: usize,
+    /// AI tools: show the prompt being typed (dimmed) in the pin bar until it is submitted.
+    pub live_prompt: bool,
+    /// Where to cut pinned prompt lines that don't fit.
+    pub truncate: Truncate,
+    /// Show the session's working directory, home as `~`, in the hint bar.
+    pub show_cwd: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Truncate {
+    /// Keep the start: "long start…".
+    #[default]
+    End,
+    /// Keep both ends: "long start…important end".
+    Middle,
+    /// Break long lines at spaces onto extra rows, up to `max_pin_rows`.
+    Wrap,
+}
+
+impl Default for BarConfig {
+    fn default() -> Self {
+        Self {
+            separator: true,
+            max_pin_rows: 4,
+            live_prompt: false,
+            truncate: Truncate::End,
+            show_cwd: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DetectConfig {
+    /// A shell counts as idle when the text before the cursor ends with one of these.
+    pub prompt_suffixes: Vec<String>,
+    /// An AI tool counts as busy while one of these shows near the bottom of its screen.
+    pub ai_busy_markers: Vec<String>,
+    /// ...and idle while one of these does. Neither leaves the state unknown.
+    pub ai_idle_markers: Vec<String>,
+}
+
+impl Default for DetectConfig {
+    fn default() -> Self {
+        Self {
+            prompt_suffixes: ["$ ", "# ", "> ", "% ", "\u{276f} "]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            ai_busy_markers: vec!["esc to interrupt".to_string()],
+            ai_idle_markers: vec!["? for shortcuts".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// Bytes sent for Shift+Enter and Alt+Enter: a newline that doesn't submit.
+    /// Tools differ; `"\u001b\r"` (ESC CR) and `"\n"` are common.
+    pub soft_newline: String,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            soft_newline: "\x1b\r".into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Shell command run (via `sh -c`) when a session's program exits, with
+    /// `MURMUR_SESSION_ID`, `MURMUR_SESSION`, `MURMUR_CWD` and `MURMUR_EXIT_CODE` set.
+    pub on_exit: Option<String>,
+}
+
+/// A `[[sessions]]` entry, also the format sessions are saved in on quit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartupSession {
+    /// Directory, with `~` expanded; relative paths start from murmur's directory.
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Program and arguments; empty runs `$SHELL`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Send a desktop notification when a background AI session starts waiting
+    /// on a permission prompt or question.
+    pub attention: bool,
+    /// Ring the bell and post an OSC 9 notification through the terminal when
+    /// a background AI session finishes its turn.
+    pub finished: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    pub backend: ClipboardBackend,
+}
+
+/// Where copy mode puts copied text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardBackend {
+    /// OSC 52 when `$SSH_TTY` is set, the system clipboard otherwise.
+    #[default]
+    Auto,
+    /// `pbcopy`, `wl-copy` or `xclip`.
+    System,
+    /// Let the terminal set the clipboard with an OSC 52 escape.
+    Osc52,
+}
+
+/// `$HOME`, or `/` when it isn't set.
+pub fn home_dir() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/".into()))
+}
+
+fn config_path() -> PathBuf {
+    let base = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(".config"));
+    base.join("murmur").join("config.toml")
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults when it does not exist.
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("{}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("{}", path.display())),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::keymap::Action;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn parse_empty_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert!(config.bar.separator);
+        assert_eq!(config.theme.separator, Theme::default().separator);
+    }
+
+    #[test]
+    fn parse_scrollback() {
+        assert_eq!(Config::parse("").unwrap().scrollback, 10_000);
+        assert_eq!(Config::parse("scrollback = 0\n").unwrap().scrollback, 0);
+    }
+
+    #[test]
+    fn parse_ai_tools() {
+        assert_eq!(Config::parse("").unwrap().ai_tools, default_ai_tools());
+        let config =
+            Config::parse("[[ai_tools]]\npattern = \"aider\"\nname = \"Aider\"\n").unwrap();
+        assert_eq!(config.ai_tools.len(), 1);
+        assert_eq!(config.ai_tools[0].name, "Aider");
+        assert_eq!(config.ai_tools[0].glyph, "");
+    }
+
+    #[test]
+    fn parse_sessions() {
+        assert!(Config::parse("").unwrap().sessions.is_empty());
+        let config = Config::parse(
+            "[[sessions]]\npath = \"~/api\"\nname = \"api\"\ncommand = [\"claude\"]\nenv = { RUST_LOG = \"debug\" }\n\n[[sessions]]\npath = \"web\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.sessions.len(), 2);
+        assert_eq!(config.sessions[0].name.as_deref(), Some("api"));
+        assert_eq!(config.sessions[0].command, ["claude"]);
+        assert_eq!(config.sessions[0].env["RUST_LOG"], "debug");
+        assert!(config.sessions[1].command.is_empty());
+    }
+
+    #[test]
+    fn parse_keymap() {
+        let config = Config::parse("[keymap]\nquit = \"Q\"\nscroll_up = \"k\"\n").unwrap();
+        assert_eq!(config.keymap.key(Action::Quit), Some(KeyCode::Char('Q')));
+        assert_eq!(config.keymap.key(Action::Pin), Some(KeyCode::Char('p')));
+        assert!(Config::parse("[keymap]\nexplode = \"x\"\n").is_err());
+        assert!(Config::parse("[keymap]\nquit = \"F13\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_mouse() {
+        assert!(Config::parse("").unwrap().mouse);
+        assert!(!Config::parse("mouse = false\n").unwrap().mouse);
+    }
+
+    #[test]
+    fn parse_clipboard_backend() {
+        assert_eq!(
+            Config::parse("").unwrap().clipboard.backend,
+            ClipboardBackend::Auto
+        );
+        let config = Config::parse("[clipboard]\nbackend = \"osc52\"\n").unwrap();
+        assert_eq!(config.clipboard.backend, ClipboardBackend::Osc52);
+    }
+
+    #[test]
+    fn parse_separator_disabled() {
+        let config = Config::parse("[bar]\nseparator = false\n").unwrap();
+        assert!(!config.bar.separator);
+    }
+
+    #[test]
+    fn parse_theme_separator_color() {
+        let config = Config::parse("[theme]\nseparator = \"38;5;240\"\n").unwrap();
+        assert_eq!(config.theme.separator, "38;5;240");
+        let config = Config::parse("[theme]\npin_text = \"1;33\"\n").unwrap();
+        assert_eq!(config.theme.pin_text, "1;33");
+        assert_eq!(config.theme.ai_accent, Theme::default().ai_accent);
+    }
+
+    #[test]
+    fn parse_glyphs() {
+        let config = Config::parse("[glyphs]\nmarker = \">\"\n").unwrap();
+        assert_eq!(config.glyphs.marker, ">");
+        assert_eq!(config.glyphs.rule, Glyphs::default().rule);
+    }
+
+    #[test]
+    fn parse_prompt_suffixes() {
+        assert!(Config::parse("")
+            .unwrap()
+            .detect
+            .prompt_suffixes
+            .contains(&"$ ".to_string()));
+        let config = Config::parse("[detect]\nprompt_suffixes = [\">>> \"]\n").unwrap();
+        assert_eq!(config.detect.prompt_suffixes, vec![">>> ".to_string()]);
+    }
+
+    #[test]
+    fn parse_focus_render() {
+        assert_eq!(
+            Config::parse("").unwrap().focus_render,
+            FocusRender::ScrollRegion
+        );
+        let config = Config::parse("focus_render = \"overlay\"\n").unwrap();
+        assert_eq!(config.focus_render, FocusRender::Overlay);
+        assert!(Config::parse("focus_render = \"tiles\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_soft_newline() {
+        assert_eq!(Config::parse("").unwrap().keys.soft_newline, "\x1b\r");
+        let config = Config::parse("[keys]\nsoft_newline = \"\\n\"\n").unwrap();
+        assert_eq!(config.keys.soft_newline, "\n");
+    }
+
+    #[test]
+    fn parse_on_exit_hook() {
+        assert!(Config::parse("").unwrap().hooks.on_exit.is_none());
+        let config = Config::parse("[hooks]\non_exit = \"notify-send done\"\n").unwrap();
+        assert_eq!(config.hooks.on_exit.as_deref(), Some("notify-send done"));
+    }
+
+    #[test]
+    fn parse_notify_attention() {
+        assert!(!Config::parse("").unwrap().notify.attention);
+        let config = Config::parse("[notify]\nattention = true\n").unwrap();
+        assert!(config.notify.attention);
+    }
+
+    #[test]
+    fn parse_exec_on_quit() {
+        assert!(Config::parse("").unwrap().exec_on_quit.is_none());
+        let config = Config::parse("exec_on_quit = \"$SHELL\"\n").unwrap();
+        assert_eq!(config.exec_on_quit.as_deref(), Some("$SHELL"));
+    }
+
+    #[test]
+    fn parse_macros() {
+        assert!(Config::parse("").unwrap().macros.is_empty());
+        let config = Config::parse("[macros]\ng = 'git status\\r'\n").unwrap();
+        assert_eq!(config.macros["g"], "git status\\r");
+    }
+
+    #[test]
+    fn parse_truncate() {
+        assert_eq!(Config::parse("").unwrap().bar.truncate, Truncate::End);
+        let config = Config::parse("[bar]\ntruncate = \"middle\"\n").unwrap();
+        assert_eq!(config.bar.truncate, Truncate::Middle);
+        let config = Config::parse("[bar]\ntruncate = \"wrap\"\n").unwrap();
+        assert_eq!(config.bar.truncate, Truncate::Wrap);
+    }
+
+    #[test]
+    fn parse_unknown_type_is_error() {
+        assert!(Config::parse("[bar]\nseparator = \"yes\"\n").is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A command sent over the control socket: `{"session": "<name>", "cmd": "..."}`,
+/// one JSON object per line. `session` is a name or a 1-based position, given as
+/// a string or a number. Other fields are ignored.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub session: String,
+    pub cmd: String,
+}
+
+/// `~/.murmur/control.sock`.
+pub fn socket_path(home: &Path) -> PathBuf {
+    home.join(".murmur").join("control.sock")
+}
+
+pub fn parse_request(line: &str) -> Result<Request, String> {
+    let mut fields = parse_object(line)?;
+    let mut field = |key: &str| {
+        let i = fields
+            .iter()
+            .position(|(name, _)| name == key)
+            .ok_or_else(|| format!("missing \"{key}\""))?;
+        Ok::<_, String>(fields.swap_remove(i).1)
+    };
+    let session = match field("session")? {
+        Value::String(text) | Value::Number(text) => text,
+        Value::Other => return Err("\"session\" must be a name or a number".into()),
+    };
+    let cmd = match field("cmd")? {
+        Value::String(text) => text,
+        _ => return Err("\"cmd\" must be a string".into()),
+    };
+    Ok(Request { session, cmd })
+}
+
+/// A value in a request object. Numbers keep their text.
+enum Value {
+    String(String),
+    Number(String),
+    /// `true`, `false` or `null`.
+    Other,
+}
+
+/// Read a flat JSON object: string keys, with strings, numbers, `true`, `false`
+/// or `null` as values. Nested objects and arrays are refused.
+fn parse_object(text: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut json = Json {
+        chars: text.chars().peekable(),
+    };
+    json.expect('{')?;
+    let mut fields = Vec::new();
+    json.skip_space();
+    if json.chars.next_if_eq(&'}').is_none() {
+        loop {
+            let key = json.string()?;
+            json.expect(':')?;
+            fields.push((key, json.value()?));
+            json.skip_space();
+            match json.chars.next() {
+                Some(',') => {}
+                Some('}') => break,
+                _ => return Err("expected ',' or '}'".into()),
+            }
+        }
+    }
+    json.skip_space();
+    match json.chars.next() {
+        None => Ok(fields),
+        Some(_) => Err("text after the object".into()),
+    }
+}
+
+struct Json<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Json<'_> {
+    fn skip_space(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, want: char) -> Result<(), String> {
+        self.skip_space();
+        match self.chars.next() {
+            Some(c) if c == want => Ok(()),
+            Some(c) => Err(format!("expected '{want}', found '{c}'")),
+            None => Err(format!("expected '{want}'")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            let c = match self.chars.next().ok_or("unterminated string")? {
+                '"' => return Ok(out),
+                '\\' => match self.chars.next().ok_or("unterminated string")? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => self.unicode_escape()?,
+                    c @ ('"' | '\\' | '/') => c,
+                    c => return Err(format!("bad escape \\{c}")),
+                },
+                c => c,
+            };
+            out.push(c);
+        }
+    }
+
+    /// The character after `\u`, joining a UTF-16 surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                    return Err("unpaired surrogate".into());
+                }
+                match self.hex4()? {
+                    low @ 0xDC00..=0xDFFF => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                    _ => return Err("unpaired surrogate".into()),
+                }
+            }
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| "unpaired surrogate".into())
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex: String = self.chars.by_ref().take(4).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 4)
+            .ok_or_else(|| format!("bad escape \\u{hex}"))
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_space();
+        let word = |json: &mut Self, part: fn(&char) -> bool| {
+            let mut word = String::new();
+            while let Some(c) = json.chars.next_if(part) {
+                word.push(c);
+            }
+            word
+        };
+        match self.chars.peek() {
+            Some('"') => self.string().map(Value::String),
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let number = word(self, |c| c.is_ascii_digit() || "+-.eE".contains(*c));
+                match number.parse::<f64>() {
+                    Ok(_) => Ok(Value::Number(number)),
+                    Err(_) => Err(format!("bad number {number}")),
+                }
+            }
+            Some('t' | 'f' | 'n') => match word(self, char::is_ascii_alphabetic).as_str() {
+                "true" | "false" | "null" => Ok(Value::Other),
+                other => Err(format!("unexpected {other}")),
+            },
+            Some('{' | '[') => Err("nested objects and arrays aren't supported".into()),
+            _ => Err("expected a value".into()),
+        }
+    }
+}
+
+/// Listens for requests, one JSON object per line. Each line is answered with `ok`
+/// or `error: <reason>`. The socket file is removed when this is dropped.
+#[cfg(unix)]
+pub struct Control {
+    listener: std::os::unix::net::UnixListener,
+    path: PathBuf,
+    /// Connected clients, each with the part of a line read so far. Nothing
+    /// blocks, so a client that stays connected can't hold up the UI.
+    clients: Vec<Client>,
+}
+
+#[cfg(unix)]
+struct Client {
+    stream: std::os::unix::net::UnixStream,
+    pending: Vec<u8>,
+}
+
+#[cfg(unix)]
+impl Control {
+    /// Longest line a client may send; past it the client is dropped.
+    const MAX_LINE: usize = 64 * 1024;
+
+    /// Listen at `path`, readable and writable by the owner only. A socket left
+    /// behind by a murmur that is still running is an error rather than taken over.
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use by another murmur", path.display()),
+            ));
+        }
+        std::fs::remove_file(path).ok();
+        // Created owner-only from the start rather than fixed up after binding
+        // SAFETY: umask takes no pointers and only swaps the file creation mask
+        let umask = unsafe { libc::umask(0o077) };
+        let listener = UnixListener::bind(path);
+        // SAFETY: as above, putting back the mask it returned
+        unsafe { libc::umask(umask) };
+        let listener = listener?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            clients: Vec::new(),
+        })
+    }
+
+    /// Accept waiting connections and pass each complete line read so far to
+    /// `dispatch`. Clients stay connected until they hang up.
+    pub fn poll(&mut self, mut dispatch: impl FnMut(Request) -> Result<(), String>) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(Client {
+                    stream,
+                    pending: Vec::new(),
+                });
+            }
+        }
+        self.clients
+            .retain_mut(|client| client.serve(&mut dispatch).is_ok());
+    }
+}
+
+#[cfg(unix)]
+impl Client {
+    /// Read what has arrived and answer each complete line. An error, or the
+    /// client hanging up, ends the connection.
+    fn serve(
+        &mut self,
+        dispatch: &mut impl FnMut(Request) -> Result<(), String>,
+    ) -> io::Result<()> {
+        use std::io::Read;
+
+        let mut buf = [0; 4096];
+        let mut read = 0;
+        let mut closed = false;
+        // Bounded so a client that never stops writing can't keep this going
+        while read < Control::MAX_LINE {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    closed = true;
+                    break;
+                }
+                Ok(n) => {
+                    self.pending.extend_from_slice(&buf[..n]);
+                    read += n;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.answer(&line, dispatch)?;
+        }
+        if closed {
+            // A last line without a newline still counts
+            let rest = std::mem::take(&mut self.pending);
+            self.answer(&rest, dispatch)?;
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if self.pending.len() > Control::MAX_LINE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+        }
+        Ok(())
+    }
+
+    fn answer(
+        &mut self,
+        line: &[u8],
+        dispatch: &mut impl FnMut(Request) -> Result<(), String>,
+    ) -> io::Result<()> {
+        use std::io::Write;
+
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        match parse_request(&line).and_then(&mut *dispatch) {
+            Ok(()) => writeln!(self.stream, "ok"),
+            Err(e) => writeln!(self.stream, "error: {e}"),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Control {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Control;
+
+#[cfg(not(unix))]
+impl Control {
+    pub fn bind(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the control socket needs a Unix system",
+        ))
+    }
+
+    pub fn poll(&mut self, _dispatch: impl FnMut(Request) -> Result<(), String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_request_reads_session_and_cmd() {
+        let request = parse_request(r#"{"session": "api", "cmd": "git pull \"origin\""}"#);
+        assert_eq!(
+            request,
+            Ok(Request {
+                session: "api".into(),
+                cmd: "git pull \"origin\"".into(),
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"session": "api"}"#),
+            Err("missing \"cmd\"".into())
+        );
+    }
+
+    #[test]
+    fn parse_request_accepts_any_json_spacing_and_numbers() {
+        let request = parse_request(
+            "  { \"cmd\" : \"echo \\u00e9\\ud83d\\ude00\\t\" ,\n\"session\":2, \"quiet\": true }  ",
+        );
+        assert_eq!(
+            request,
+            Ok(Request {
+                session: "2".into(),
+                cmd: "echo \u{e9}\u{1f600}\t".into(),
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"session": null, "cmd": "ls"}"#),
+            Err("\"session\" must be a name or a number".into())
+        );
+        assert_eq!(
+            parse_request(r#"{"session": "api", "cmd": ["ls"]}"#),
+            Err("nested objects and arrays aren't supported".into())
+        );
+        assert!(parse_request(r#"{"session": "api", "cmd": "ls"} x"#).is_err());
+        assert!(parse_request(r#"{"session": "api", "cmd": "ls"#).is_err());
+        assert!(parse_request("session=api").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn control_answers_each_line() {
+        use std::io::{Read, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir()
+            .join(format!("murmur-control-{}", std::process::id()))
+            .join("control.sock");
+        let mut control = Control::bind(&path).unwrap();
+        assert!(Control::bind(&path).is_err());
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0);
+        }
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client
+            .write_all(
+                b"{\"session\":\"web\",\"cmd\":\"ls\"}\n{\"session\":\"nope\",\"cmd\":\"ls\"}\n",
+            )
+            .unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+        let mut seen = Vec::new();
+        control.poll(|request| {
+            seen.push(request.cmd);
+            match request.session.as_str() {
+                "web" => Ok(()),
+                other => Err(format!("no session named {other}")),
+            }
+        });
+        let mut replies = String::new();
+        client.read_to_string(&mut replies).unwrap();
+        assert_eq!(seen, ["ls", "ls"]);
+        assert_eq!(replies, "ok\nerror: no session named nope\n");
+
+        drop(control);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn control_keeps_partial_lines_across_polls() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir()
+            .join(format!("murmur-control-partial-{}", std::process::id()))
+            .join("control.sock");
+        let mut control = Control::bind(&path).unwrap();
+        let mut client = UnixStream::connect(&path).unwrap();
+        let mut seen = Vec::new();
+
+        // A client that stays connected mid-line doesn't hold up the poll
+        client.write_all(b"{\"session\":\"web\",").unwrap();
+        control.poll(|request| {
+            seen.push(request.cmd);
+            Ok(())
+        });
+        assert!(seen.is_empty());
+
+        client.write_all(b"\"cmd\":\"ls\"}\n").unwrap();
+        control.poll(|request| {
+            seen.push(request.cmd);
+            Ok(())
+        });
+        assert_eq!(seen, ["ls"]);
+        let mut reply = String::new();
+        BufReader::new(&client).read_line(&mut reply).unwrap();
+        assert_eq!(reply, "ok\n");
+
+        drop(control);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,96 @@
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+
+use crate::session::Session;
+
+/// Build the on-exit hook: `command` runs under `sh -c` with the session
+/// described in `MURMUR_SESSION_ID`, `MURMUR_SESSION`, `MURMUR_CWD` and
+/// `MURMUR_EXIT_CODE`.
+fn exit_hook_command(command: &str, id: u64, name: &str, cwd: &str, code: u32) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .env("MURMUR_SESSION_ID", id.to_string())
+        .env("MURMUR_SESSION", name)
+        .env("MURMUR_CWD", cwd)
+        .env("MURMUR_EXIT_CODE", code.to_string());
+    cmd
+}
+
+/// Build a desktop notification: `osascript` on macOS, `notify-send` elsewhere.
+fn notification_command(title: &str, body: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let quote = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            quote(body),
+            quote(title)
+        );
+        let mut cmd = Command::new("osascript");
+        cmd.arg("-e").arg(script);
+        cmd
+    } else {
+        let mut cmd = Command::new("notify-send");
+        cmd.arg(title).arg(body);
+        cmd
+    }
+}
+
+/// Start `cmd` without waiting for it. A background thread reaps it, so a
+/// command that hangs never blocks the UI.
+fn spawn_detached(mut cmd: Command) -> Result<()> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+pub fn spawn_exit_hook(command: &str, session: &Session, code: u32) -> Result<()> {
+    spawn_detached(exit_hook_command(
+        command,
+        session.id,
+        &session.name,
+        session.project_path(),
+        code,
+    ))
+}
+
+pub fn desktop_notification(title: &str, body: &str) -> Result<()> {
+    spawn_detached(notification_command(title, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn exit_hook_env() {
+        let cmd = exit_hook_command("true", 7, "api", "/tmp/api", 1);
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("MURMUR_SESSION_ID"), Some(OsStr::new("7")))));
+        assert!(envs.contains(&(OsStr::new("MURMUR_SESSION"), Some(OsStr::new("api")))));
+        assert!(envs.contains(&(OsStr::new("MURMUR_CWD"), Some(OsStr::new("/tmp/api")))));
+        assert!(envs.contains(&(OsStr::new("MURMUR_EXIT_CODE"), Some(OsStr::new("1")))));
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, vec!["-c", "true"]);
+    }
+
+    #[test]
+    fn notification_includes_title_and_body() {
+        let cmd = notification_command("murmur", "api needs \"attention\"");
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        let joined = args.join(" ");
+        assert!(joined.contains("murmur"));
+        assert!(joined.contains("api needs"));
+    }
+}
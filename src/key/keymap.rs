@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+/// A command run by pressing its key after the prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    PrevPin,
+    NextPin,
+    Pin,
+    Unpin,
+    Note,
+    NewSession,
+    /// Close the session, after a yes/no question.
+    Close,
+    /// SIGINT to the foreground process group.
+    Interrupt,
+    /// Start or stop recording the session to an asciicast file.
+    Record,
+    /// Read the config file again.
+    Reload,
+    /// Send typed input to every session, or stop.
+    Broadcast,
+    FindSession,
+    Rename,
+    NextSession,
+    PrevSession,
+    MoveLeft,
+    MoveRight,
+    Split,
+    /// Move input to the other half of a split.
+    OtherPane,
+    ScrollUp,
+    ScrollDown,
+    Copy,
+    Paste,
+    Search,
+    Export,
+    ExportAnsi,
+    Info,
+    Pause,
+    Update,
+    Quit,
+}
+
+/// Prefix key bindings: the defaults, with any `[keymap]` entries from the
+/// config replacing them. `1`–`9` and the prefix itself are fixed; binding a
+/// digit is an error.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "HashMap<Action, String>")]
+pub struct Keymap {
+    bindings: Vec<(Action, KeyCode)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+        let bindings = [
+            (PrevPin, KeyCode::Char('[')),
+            (NextPin, KeyCode::Char(']')),
+            (Pin, KeyCode::Char('p')),
+            (Unpin, KeyCode::Char('x')),
+            (Note, KeyCode::Char('m')),
+            (NewSession, KeyCode::Char('c')),
+            (Close, KeyCode::Char('d')),
+            (Interrupt, KeyCode::Char('C')),
+            (Record, KeyCode::Char('R')),
+            (Reload, KeyCode::Char('r')),
+            (Broadcast, KeyCode::Char('b')),
+            (FindSession, KeyCode::Char('f')),
+            (Rename, KeyCode::Char(',')),
+            (NextSession, KeyCode::Tab),
+            (PrevSession, KeyCode::BackTab),
+            (MoveLeft, KeyCode::Char('<')),
+            (MoveRight, KeyCode::Char('>')),
+            (Split, KeyCode::Char('|')),
+            (OtherPane, KeyCode::Char(' ')),
+            (ScrollUp, KeyCode::PageUp),
+            (ScrollDown, KeyCode::PageDown),
+            (Copy, KeyCode::Char('v')),
+            (Paste, KeyCode::Char('P')),
+            (Search, KeyCode::Char('/')),
+            (Export, KeyCode::Char('e')),
+            (ExportAnsi, KeyCode::Char('E')),
+            (Info, KeyCode::Char('i')),
+            (Pause, KeyCode::Char('s')),
+            (Update, KeyCode::Char('u')),
+            (Quit, KeyCode::Char('q')),
+        ];
+        Self {
+            bindings: bindings.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<HashMap<Action, String>> for Keymap {
+    type Error = String;
+
+    fn try_from(overrides: HashMap<Action, String>) -> Result<Self, Self::Error> {
+        let mut keys = HashMap::new();
+        for (action, name) in &overrides {
+            let code = parse_key(name).ok_or_else(|| format!("unknown key {name:?}"))?;
+            if matches!(code, KeyCode::Char('1'..='9')) {
+                return Err(format!("{name:?} is kept for switching sessions"));
+            }
+            if let Some(other) = keys.insert(code, *action) {
+                return Err(format!(
+                    "{name:?} is bound to both {other:?} and {action:?}"
+                ));
+            }
+        }
+        let mut keymap = Self::default();
+        // A default whose key was taken by an override is left unbound
+        keymap
+            .bindings
+            .retain(|(action, code)| !overrides.contains_key(action) && !keys.contains_key(code));
+        keymap
+            .bindings
+            .extend(keys.into_iter().map(|(code, action)| (action, code)));
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == code)
+            .map(|(action, _)| *action)
+    }
+
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, code)| *code)
+    }
+
+    /// The key list shown while the prefix is armed. Unbound actions are left out.
+    pub fn hint(&self, update_available: bool) -> String {
+        use Action::*;
+        let name = |action| self.key(action).map(key_name);
+        // Actions that go either way share one entry, shown as "a/b: label"
+        let entries: [(&[Action], &str); 23] = [
+            (&[PrevPin, NextPin], "pins"),
+            (&[Pin], "pin"),
+            (&[Unpin], "unpin"),
+            (&[Note], "note"),
+            (&[NewSession], "new"),
+            (&[Close], "close"),
+            (&[NextSession, PrevSession], "switch"),
+            (&[FindSession], "find"),
+            (&[MoveLeft, MoveRight], "move"),
+            (&[Split], "split"),
+            (&[OtherPane], "other half"),
+            (&[Rename], "rename"),
+            (&[ScrollUp, ScrollDown], "scroll"),
+            (&[Copy], "copy"),
+            (&[Paste], "paste"),
+            (&[Search], "search"),
+            (&[Export, ExportAnsi], "export"),
+            (&[Record], "record"),
+            (&[Broadcast], "broadcast"),
+            (&[Interrupt], "interrupt"),
+            (&[Reload], "reload"),
+            (&[Info], "info"),
+            (&[Pause], "pause"),
+        ];
+        let mut items = Vec::new();
+        for (actions, label) in entries {
+            let mut keys: Vec<String> = actions.iter().filter_map(|&action| name(action)).collect();
+            // The digits switch sessions whatever the others are bound to
+            if actions == [NextSession, PrevSession] {
+                keys.push("1-9".to_string());
+            }
+            if !keys.is_empty() {
+                items.push(format!("{}: {label}", keys.join("/")));
+            }
+        }
+        let tail = [(update_available, Update, "update"), (true, Quit, "quit")];
+        for (shown, action, label) in tail {
+            if let Some(key) = name(action).filter(|_| shown) {
+                items.push(format!("{key}: {label}"));
+            }
+        }
+        items.push("Ctrl+\\: send Ctrl+\\".to_string());
+        items.join("  ")
+    }
+}
+
+/// Parse a key as written in the config: a single character, or one of
+/// `Space`, `Tab`, `BackTab`, `PageUp`, `PageDown`.
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    match name.to_ascii_lowercase().as_str() {
+        "space" => Some(KeyCode::Char(' ')),
+        "tab" => Some(KeyCode::Tab),
+        "backtab" | "shift+tab" => Some(KeyCode::BackTab),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        _ => None,
+    }
+}
+
+/// How a key is written in the hint bar.
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".into(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Tab => "Tab".into(),
+        KeyCode::BackTab => "S-Tab".into(),
+        KeyCode::PageUp => "PgUp".into(),
+        KeyCode::PageDown => "PgDn".into(),
+        other => format!("{other:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(overrides: &[(Action, &str)]) -> Result<Keymap, String> {
+        let overrides = overrides
+            .iter()
+            .map(|(action, key)| (*action, key.to_string()))
+            .collect::<HashMap<_, _>>();
+        Keymap::try_from(overrides)
+    }
+
+    #[test]
+    fn default_hint_lists_every_command() {
+        assert_eq!(
+            Keymap::default().hint(true),
+            "[/]: pins  p: pin  x: unpin  m: note  c: new  d: close  Tab/S-Tab/1-9: switch  f: find  </>: move  |: split  Space: other half  ,: rename  PgUp/PgDn: scroll  v: copy  P: paste  /: search  e/E: export  R: record  b: broadcast  C: interrupt  r: reload  i: info  s: pause  u: update  q: quit  Ctrl+\\: send Ctrl+\\"
+        );
+        assert!(!Keymap::default().hint(false).contains("update"));
+        let keymap = keymap(&[(Action::PrevSession, "z")]).unwrap();
+        assert!(keymap.hint(false).contains("Tab/z/1-9: switch"));
+    }
+
+    #[test]
+    fn override_replaces_default() {
+        let keymap = keymap(&[(Action::Quit, "Q"), (Action::ScrollUp, "k")]).unwrap();
+        assert_eq!(keymap.action(KeyCode::Char('Q')), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('q')), None);
+        assert_eq!(keymap.action(KeyCode::Char('k')), Some(Action::ScrollUp));
+        assert_eq!(keymap.action(KeyCode::PageUp), None);
+        assert!(keymap.hint(false).contains("k/PgDn: scroll"));
+        assert!(keymap.hint(false).contains("Q: quit"));
+    }
+
+    #[test]
+    fn override_takes_key_from_default() {
+        let keymap = keymap(&[(Action::Quit, "x")]).unwrap();
+        assert_eq!(keymap.action(KeyCode::Char('x')), Some(Action::Quit));
+        assert_eq!(keymap.key(Action::Unpin), None);
+        assert!(!keymap.hint(false).contains("unpin"));
+    }
+
+    #[test]
+    fn rejects_duplicate_and_unknown_keys() {
+        assert!(keymap(&[(Action::Quit, "z"), (Action::Pause, "z")]).is_err());
+        assert!(keymap(&[(Action::Quit, "Ctrl+Q")]).is_err());
+        assert!(keymap(&[(Action::Pin, "1")]).is_err());
+        assert!(keymap(&[(Action::Pin, "0")]).is_ok());
+    }
+
+    #[test]
+    fn parse_named_keys() {
+        assert_eq!(parse_key("Space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("pageup"), Some(KeyCode::PageUp));
+        assert_eq!(parse_key("Shift+Tab"), Some(KeyCode::BackTab));
+        assert_eq!(parse_key("\u{e9}"), Some(KeyCode::Char('\u{e9}')));
+        assert_eq!(parse_key(""), None);
+    }
+}
@@ -1,18 +1,156 @@
+pub mod keymap;
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+/// Byte sent for the prefix key, Ctrl+\.
+pub const PREFIX_BYTE: u8 = 0x1c;
+
+/// Control byte for Ctrl+`c`. Besides letters this covers the punctuation
+/// controls and their legacy digit aliases (Ctrl+4 is Ctrl+\, etc.), which is
+/// how crossterm decodes those bytes without the kitty keyboard protocol.
 fn ctrl_byte(c: char) -> Option<u8> {
     let b = c.to_ascii_lowercase() as u8;
-    b.is_ascii_lowercase().then(|| b - b'a' + 1)
+    if b.is_ascii_lowercase() {
+        return Some(b - b'a' + 1);
+    }
+    match c {
+        ' ' | '@' | '2' => Some(0x00),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '/' | '7' => Some(0x1f),
+        '8' => Some(0x7f),
+        _ => None,
+    }
+}
+
+/// Whether `key` is the prefix. Compared by the byte it would send rather than
+/// by key code, because crossterm reports Ctrl+\ as `Char('\\')` with the kitty
+/// protocol but as `Char('4')` when decoding the legacy 0x1c byte. A real Ctrl+4
+/// is indistinguishable from Ctrl+\ on legacy terminals (both send 0x1c), so it
+/// acts as the prefix too.
+pub fn is_prefix_key(key: &KeyEvent) -> bool {
+    !key.modifiers.contains(KeyModifiers::ALT)
+        && key_event_to_bytes(key, false).is_some_and(|b| b == [PREFIX_BYTE])
+}
+
+/// Shift+Enter or Alt+Enter: insert a newline without submitting.
+pub fn is_soft_newline(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Enter
+        && key
+            .modifiers
+            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT)
+}
+
+/// Expand a macro definition into the bytes to send. Understands `\r`, `\n`,
+/// `\t`, `\e` (ESC) and `\\`; any other backslash is kept as is.
+pub fn macro_bytes(def: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(def.len());
+    let mut chars = def.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('r') => out.push(b'\r'),
+            Some('n') => out.push(b'\n'),
+            Some('t') => out.push(b'\t'),
+            Some('e') => out.push(0x1b),
+            Some('\\') => out.push(b'\\'),
+            Some(other) => {
+                out.push(b'\\');
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => out.push(b'\\'),
+        }
+    }
+    out
+}
+
+/// xterm modifier parameter: 1 + (Shift=1 | Alt=2 | Ctrl=4).
+fn modifier_param(modifiers: KeyModifiers) -> u8 {
+    let mut param = 1;
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        param += 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        param += 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        param += 4;
+    }
+    param
 }
 
-/// Convert a crossterm KeyEvent to raw bytes suitable for PTY input.
-pub fn key_event_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
+/// Encode F1–F24 the way xterm does. Modifiers go in the sequence itself
+/// (`CSI 1;<mod>P` for F1–F4, `CSI <n>;<mod>~` otherwise), and F13–F24 are
+/// sent as Shift+F1–F12.
+fn function_key_bytes(n: u8, modifiers: KeyModifiers) -> Option<Vec<u8>> {
+    let (n, modifiers) = match n {
+        13..=24 => (n - 12, modifiers | KeyModifiers::SHIFT),
+        _ => (n, modifiers),
+    };
+    let param = modifier_param(modifiers);
+    let seq = match n {
+        1..=4 => {
+            let final_byte = b"PQRS"[(n - 1) as usize] as char;
+            if param == 1 {
+                format!("\x1bO{final_byte}")
+            } else {
+                format!("\x1b[1;{param}{final_byte}")
+            }
+        }
+        5..=12 => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][(n - 5) as usize];
+            if param == 1 {
+                format!("\x1b[{code}~")
+            } else {
+                format!("\x1b[{code};{param}~")
+            }
+        }
+        _ => return None,
+    };
+    Some(seq.into_bytes())
+}
+
+/// Encode a cursor or editing key: `CSI <final>` (or `CSI <n>~` when `code` is
+/// given) unmodified, and xterm's `CSI 1;<mod><final>` / `CSI <n>;<mod>~` with
+/// modifiers, e.g. `CSI 1;5C` for Ctrl+Right.
+fn csi_key_bytes(final_byte: char, code: Option<u8>, modifiers: KeyModifiers) -> Vec<u8> {
+    let param = modifier_param(modifiers);
+    let seq = match (code, param) {
+        (None, 1) => format!("\x1b[{final_byte}"),
+        (None, _) => format!("\x1b[1;{param}{final_byte}"),
+        (Some(n), 1) => format!("\x1b[{n}~"),
+        (Some(n), _) => format!("\x1b[{n};{param}~"),
+    };
+    seq.into_bytes()
+}
+
+/// Encode a Ctrl combination with no control byte (Ctrl+9, Ctrl+.) the kitty way,
+/// `CSI <codepoint>;<mod>u`.
+fn csi_u_bytes(c: char, modifiers: KeyModifiers) -> Vec<u8> {
+    format!("\x1b[{};{}u", c as u32, modifier_param(modifiers)).into_bytes()
+}
+
+/// Convert a crossterm KeyEvent to raw bytes suitable for PTY input. `kitty` is
+/// whether the program turned on the kitty keyboard protocol; only then are
+/// Ctrl keys without a control byte sent, as CSI u, since other programs would
+/// read the sequence as typed text.
+pub fn key_event_to_bytes(key: &KeyEvent, kitty: bool) -> Option<Vec<u8>> {
     let alt = key.modifiers.contains(KeyModifiers::ALT);
 
     let mut bytes = match key.code {
         KeyCode::Char(c) => {
             if key.modifiers.contains(KeyModifiers::CONTROL) {
-                vec![ctrl_byte(c)?]
+                match ctrl_byte(c) {
+                    Some(b) => vec![b],
+                    None => return kitty.then(|| csi_u_bytes(c, key.modifiers)),
+                }
             } else {
                 let mut buf = [0u8; 4];
                 let s = c.encode_utf8(&mut buf);
@@ -24,28 +162,18 @@ pub fn key_event_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
         KeyCode::Tab => vec![b'\t'],
         KeyCode::BackTab => b"\x1b[Z".to_vec(),
         KeyCode::Esc => vec![0x1b],
-        KeyCode::Up => b"\x1b[A".to_vec(),
-        KeyCode::Down => b"\x1b[B".to_vec(),
-        KeyCode::Right => b"\x1b[C".to_vec(),
-        KeyCode::Left => b"\x1b[D".to_vec(),
-        KeyCode::Home => b"\x1b[H".to_vec(),
-        KeyCode::End => b"\x1b[F".to_vec(),
-        KeyCode::Delete => b"\x1b[3~".to_vec(),
-        KeyCode::PageUp => b"\x1b[5~".to_vec(),
-        KeyCode::PageDown => b"\x1b[6~".to_vec(),
-        KeyCode::Insert => b"\x1b[2~".to_vec(),
-        KeyCode::F(1) => b"\x1bOP".to_vec(),
-        KeyCode::F(2) => b"\x1bOQ".to_vec(),
-        KeyCode::F(3) => b"\x1bOR".to_vec(),
-        KeyCode::F(4) => b"\x1bOS".to_vec(),
-        KeyCode::F(5) => b"\x1b[15~".to_vec(),
-        KeyCode::F(6) => b"\x1b[17~".to_vec(),
-        KeyCode::F(7) => b"\x1b[18~".to_vec(),
-        KeyCode::F(8) => b"\x1b[19~".to_vec(),
-        KeyCode::F(9) => b"\x1b[20~".to_vec(),
-        KeyCode::F(10) => b"\x1b[21~".to_vec(),
-        KeyCode::F(11) => b"\x1b[23~".to_vec(),
-        KeyCode::F(12) => b"\x1b[24~".to_vec(),
+        // Modifiers, Alt included, go in the sequence itself
+        KeyCode::Up => return Some(csi_key_bytes('A', None, key.modifiers)),
+        KeyCode::Down => return Some(csi_key_bytes('B', None, key.modifiers)),
+        KeyCode::Right => return Some(csi_key_bytes('C', None, key.modifiers)),
+        KeyCode::Left => return Some(csi_key_bytes('D', None, key.modifiers)),
+        KeyCode::Home => return Some(csi_key_bytes('H', None, key.modifiers)),
+        KeyCode::End => return Some(csi_key_bytes('F', None, key.modifiers)),
+        KeyCode::Delete => return Some(csi_key_bytes('~', Some(3), key.modifiers)),
+        KeyCode::PageUp => return Some(csi_key_bytes('~', Some(5), key.modifiers)),
+        KeyCode::PageDown => return Some(csi_key_bytes('~', Some(6), key.modifiers)),
+        KeyCode::Insert => return Some(csi_key_bytes('~', Some(2), key.modifiers)),
+        KeyCode::F(n) => return function_key_bytes(n, key.modifiers),
         _ => return None,
     };
 
@@ -56,6 +184,33 @@ pub fn key_event_to_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
     Some(bytes)
 }
 
+/// Bytes for pasting `text` into a program. Programs that turned on bracketed
+/// paste get it between `CSI 200~` and `CSI 201~`, with any end marker inside
+/// the text removed so the paste can't end early.
+pub fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    if !bracketed {
+        return text.as_bytes().to_vec();
+    }
+    let text = text.replace("\x1b[201~", "");
+    format!("\x1b[200~{text}\x1b[201~").into_bytes()
+}
+
+/// Encode a wheel notch at 0-indexed `col`/`row` as an SGR mouse report
+/// (`CSI < button;col;row M`), button 64 for up and 65 for down.
+pub fn wheel_bytes(up: bool, col: u16, row: u16, modifiers: KeyModifiers) -> Vec<u8> {
+    let mut button = if up { 64 } else { 65 };
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        button += 4;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        button += 8;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        button += 16;
+    }
+    format!("\x1b[<{button};{};{}M", col + 1, row + 1).into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,92 +223,245 @@ mod tests {
     #[test]
     fn bytes_char() {
         let key = make_key(KeyCode::Char('a'), KeyModifiers::NONE);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![b'a']));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![b'a']));
     }
 
     #[test]
     fn bytes_ctrl_c() {
         let key = make_key(KeyCode::Char('c'), KeyModifiers::CONTROL);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![3]));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![3]));
     }
 
     #[test]
     fn bytes_ctrl_a() {
         let key = make_key(KeyCode::Char('a'), KeyModifiers::CONTROL);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![1]));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![1]));
     }
 
     #[test]
     fn bytes_alt_a() {
         let key = make_key(KeyCode::Char('a'), KeyModifiers::ALT);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![0x1b, b'a']));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![0x1b, b'a']));
     }
 
     #[test]
     fn bytes_enter() {
         let key = make_key(KeyCode::Enter, KeyModifiers::NONE);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![b'\r']));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![b'\r']));
     }
 
     #[test]
     fn bytes_special_keys() {
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Up, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Up, KeyModifiers::NONE), false),
             Some(b"\x1b[A".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Down, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Down, KeyModifiers::NONE), false),
             Some(b"\x1b[B".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Right, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Right, KeyModifiers::NONE), false),
             Some(b"\x1b[C".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Left, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Left, KeyModifiers::NONE), false),
             Some(b"\x1b[D".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Home, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Home, KeyModifiers::NONE), false),
             Some(b"\x1b[H".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::End, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::End, KeyModifiers::NONE), false),
             Some(b"\x1b[F".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::Delete, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::Delete, KeyModifiers::NONE), false),
             Some(b"\x1b[3~".to_vec())
         );
     }
 
+    #[test]
+    fn bytes_modified_cursor_keys() {
+        let bytes =
+            |code, modifiers| key_event_to_bytes(&make_key(code, modifiers), false).unwrap();
+        assert_eq!(bytes(KeyCode::Right, KeyModifiers::CONTROL), b"\x1b[1;5C");
+        assert_eq!(bytes(KeyCode::Left, KeyModifiers::CONTROL), b"\x1b[1;5D");
+        assert_eq!(bytes(KeyCode::Up, KeyModifiers::SHIFT), b"\x1b[1;2A");
+        assert_eq!(bytes(KeyCode::Down, KeyModifiers::ALT), b"\x1b[1;3B");
+        assert_eq!(
+            bytes(KeyCode::End, KeyModifiers::SHIFT | KeyModifiers::CONTROL),
+            b"\x1b[1;6F"
+        );
+        assert_eq!(bytes(KeyCode::Home, KeyModifiers::CONTROL), b"\x1b[1;5H");
+        assert_eq!(bytes(KeyCode::Delete, KeyModifiers::CONTROL), b"\x1b[3;5~");
+        assert_eq!(bytes(KeyCode::PageUp, KeyModifiers::SHIFT), b"\x1b[5;2~");
+    }
+
     #[test]
     fn bytes_f_keys() {
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::F(1), KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::F(1), KeyModifiers::NONE), false),
             Some(b"\x1bOP".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::F(5), KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::F(5), KeyModifiers::NONE), false),
             Some(b"\x1b[15~".to_vec())
         );
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::F(12), KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::F(12), KeyModifiers::NONE), false),
             Some(b"\x1b[24~".to_vec())
         );
     }
 
+    #[test]
+    fn bytes_modified_f_keys() {
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(5), KeyModifiers::SHIFT), false),
+            Some(b"\x1b[15;2~".to_vec())
+        );
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(12), KeyModifiers::CONTROL), false),
+            Some(b"\x1b[24;5~".to_vec())
+        );
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(1), KeyModifiers::CONTROL), false),
+            Some(b"\x1b[1;5P".to_vec())
+        );
+        // Alt is part of the modifier parameter, not an ESC prefix
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(4), KeyModifiers::ALT), false),
+            Some(b"\x1b[1;3S".to_vec())
+        );
+    }
+
+    #[test]
+    fn bytes_high_f_keys() {
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(13), KeyModifiers::NONE), false),
+            Some(b"\x1b[1;2P".to_vec())
+        );
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(24), KeyModifiers::NONE), false),
+            Some(b"\x1b[24;2~".to_vec())
+        );
+        assert_eq!(
+            key_event_to_bytes(&make_key(KeyCode::F(25), KeyModifiers::NONE), false),
+            None
+        );
+    }
+
+    #[test]
+    fn soft_newline_keys() {
+        assert!(is_soft_newline(&make_key(
+            KeyCode::Enter,
+            KeyModifiers::SHIFT
+        )));
+        assert!(is_soft_newline(&make_key(
+            KeyCode::Enter,
+            KeyModifiers::ALT
+        )));
+        assert!(!is_soft_newline(&make_key(
+            KeyCode::Enter,
+            KeyModifiers::NONE
+        )));
+        assert!(!is_soft_newline(&make_key(
+            KeyCode::Char('j'),
+            KeyModifiers::SHIFT
+        )));
+    }
+
+    #[test]
+    fn bytes_ctrl_punctuation() {
+        let ctrl =
+            |c| key_event_to_bytes(&make_key(KeyCode::Char(c), KeyModifiers::CONTROL), false);
+        assert_eq!(ctrl(' '), Some(vec![0x00]));
+        assert_eq!(ctrl('@'), Some(vec![0x00]));
+        assert_eq!(ctrl('['), Some(vec![0x1b]));
+        assert_eq!(ctrl('\\'), Some(vec![0x1c]));
+        assert_eq!(ctrl(']'), Some(vec![0x1d]));
+        assert_eq!(ctrl('^'), Some(vec![0x1e]));
+        assert_eq!(ctrl('/'), Some(vec![0x1f]));
+        assert_eq!(ctrl('_'), Some(vec![0x1f]));
+        assert_eq!(ctrl('9'), None);
+    }
+
+    #[test]
+    fn bytes_ctrl_without_control_byte_use_csi_u() {
+        let bytes = |c, modifiers| key_event_to_bytes(&make_key(KeyCode::Char(c), modifiers), true);
+        assert_eq!(
+            bytes('1', KeyModifiers::CONTROL),
+            Some(b"\x1b[49;5u".to_vec())
+        );
+        assert_eq!(
+            bytes('.', KeyModifiers::CONTROL | KeyModifiers::ALT),
+            Some(b"\x1b[46;7u".to_vec())
+        );
+        // Keys with a control byte keep it
+        assert_eq!(bytes('i', KeyModifiers::CONTROL), Some(vec![b'\t']));
+    }
+
+    #[test]
+    fn bytes_ctrl_without_control_byte_dropped_without_kitty() {
+        let key = make_key(KeyCode::Char('.'), KeyModifiers::CONTROL);
+        assert_eq!(key_event_to_bytes(&key, false), None);
+        let key = make_key(KeyCode::Char('1'), KeyModifiers::CONTROL);
+        assert_eq!(key_event_to_bytes(&key, false), None);
+    }
+
+    #[test]
+    fn prefix_key_matrix() {
+        // crossterm 0.29 with kitty flags
+        assert!(is_prefix_key(&make_key(
+            KeyCode::Char('\\'),
+            KeyModifiers::CONTROL
+        )));
+        // legacy 0x1c byte, as decoded by crossterm
+        assert!(is_prefix_key(&make_key(
+            KeyCode::Char('4'),
+            KeyModifiers::CONTROL
+        )));
+        // plain keys and other controls
+        assert!(!is_prefix_key(&make_key(
+            KeyCode::Char('4'),
+            KeyModifiers::NONE
+        )));
+        assert!(!is_prefix_key(&make_key(
+            KeyCode::Char('\\'),
+            KeyModifiers::NONE
+        )));
+        assert!(!is_prefix_key(&make_key(
+            KeyCode::Char('a'),
+            KeyModifiers::CONTROL
+        )));
+        assert!(!is_prefix_key(&make_key(
+            KeyCode::Char('\\'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )));
+    }
+
+    #[test]
+    fn macro_escapes() {
+        assert_eq!(macro_bytes("git status\\r"), b"git status\r");
+        assert_eq!(macro_bytes("\\e[A\\t\\n"), b"\x1b[A\t\n");
+        assert_eq!(macro_bytes("a\\\\b"), b"a\\b");
+        assert_eq!(macro_bytes("\\x"), b"\\x");
+        assert_eq!(macro_bytes("end\\"), b"end\\");
+        assert_eq!(macro_bytes("\u{4F60}"), "\u{4F60}".as_bytes());
+    }
+
     #[test]
     fn bytes_utf8() {
         let key = make_key(KeyCode::Char('\u{FF21}'), KeyModifiers::NONE); // Fullwidth A
-        let bytes = key_event_to_bytes(&key).unwrap();
+        let bytes = key_event_to_bytes(&key, false).unwrap();
         assert_eq!(bytes, "\u{FF21}".as_bytes());
     }
 
     #[test]
     fn bytes_none_cases() {
         assert_eq!(
-            key_event_to_bytes(&make_key(KeyCode::CapsLock, KeyModifiers::NONE)),
+            key_event_to_bytes(&make_key(KeyCode::CapsLock, KeyModifiers::NONE), false),
             None
         );
     }
@@ -161,18 +469,37 @@ mod tests {
     #[test]
     fn bytes_backspace() {
         let key = make_key(KeyCode::Backspace, KeyModifiers::NONE);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![0x7f]));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![0x7f]));
     }
 
     #[test]
     fn bytes_tab() {
         let key = make_key(KeyCode::Tab, KeyModifiers::NONE);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![b'\t']));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![b'\t']));
     }
 
     #[test]
     fn bytes_esc() {
         let key = make_key(KeyCode::Esc, KeyModifiers::NONE);
-        assert_eq!(key_event_to_bytes(&key), Some(vec![0x1b]));
+        assert_eq!(key_event_to_bytes(&key, false), Some(vec![0x1b]));
+    }
+
+    #[test]
+    fn paste_wraps_only_when_bracketed() {
+        assert_eq!(paste_bytes("ls\n", false), b"ls\n");
+        assert_eq!(paste_bytes("ls\n", true), b"\x1b[200~ls\n\x1b[201~");
+        assert_eq!(paste_bytes("a\x1b[201~b", true), b"\x1b[200~ab\x1b[201~");
+    }
+
+    #[test]
+    fn wheel_sgr_reports() {
+        assert_eq!(
+            wheel_bytes(true, 0, 0, KeyModifiers::NONE),
+            b"\x1b[<64;1;1M"
+        );
+        assert_eq!(
+            wheel_bytes(false, 9, 4, KeyModifiers::CONTROL),
+            b"\x1b[<81;10;5M"
+        );
     }
 }
@@ -1,14 +1,38 @@
-/// Compute how many rows the bottom bar area occupies (separator + PIN lines + hint bar).
-pub fn focus_bar_rows(pinned_prompt: &str, is_ai_tool: bool) -> u16 {
-    if !is_ai_tool {
-        return 2; // separator + hint bar
+use crate::config::{BarConfig, FocusRender};
+use crate::ui::ansi;
+
+/// What decides the height of a session's bars.
+pub struct BarContent<'a> {
+    pub pinned_prompt: &'a str,
+    pub is_ai_tool: bool,
+    pub has_note: bool,
+    /// Terminal width, which decides how many rows a wrapped prompt takes.
+    pub cols: u16,
+}
+
+/// Compute how many rows the bottom bar area occupies
+/// (separator + note + PIN lines + hint bar).
+pub fn focus_bar_rows(content: &BarContent, bar: &BarConfig) -> u16 {
+    let separator = u16::from(bar.separator) + u16::from(content.has_note);
+    if !content.is_ai_tool {
+        return separator + 1; // separator + hint bar
     }
-    let pin_lines = if pinned_prompt.is_empty() {
+    let pin_lines = if content.pinned_prompt.is_empty() {
         1
     } else {
-        (pinned_prompt.bytes().filter(|&b| b == b'\n').count() + 1) as u16
+        let lines = ansi::pin_lines(content.pinned_prompt, content.cols, bar.truncate).len();
+        lines.min(bar.max_pin_rows.max(1)) as u16
     };
-    pin_lines + 2 // separator + pin lines + hint bar
+    separator + pin_lines + 1 // separator + pin lines + hint bar
+}
+
+/// Rows given to the program. Overlay mode runs it at full height underneath the bars.
+/// Never zero, so the scroll region stays valid on tiny terminals.
+pub fn term_rows(rows: u16, bar_rows: u16, render: FocusRender) -> u16 {
+    match render {
+        FocusRender::ScrollRegion => rows.saturating_sub(bar_rows).max(1),
+        FocusRender::Overlay => rows,
+    }
 }
 
 /// Result of a bar height change, describing which rows to clear and the new terminal height.
@@ -24,10 +48,10 @@ pub struct BarResize {
 pub fn compute_bar_resize(
     rows: u16,
     old_bar_rows: u16,
-    pinned_prompt: &str,
-    is_ai_tool: bool,
+    content: &BarContent,
+    bar: &BarConfig,
 ) -> Option<BarResize> {
-    let new_bar_rows = focus_bar_rows(pinned_prompt, is_ai_tool);
+    let new_bar_rows = focus_bar_rows(content, bar);
     if new_bar_rows == old_bar_rows {
         return None;
     }
@@ -37,45 +61,86 @@ pub fn compute_bar_resize(
         new_bar_rows,
         clear_from: old_bar_start.min(new_bar_start),
         clear_to: rows,
-        term_rows: rows.saturating_sub(new_bar_rows),
+        term_rows: term_rows(rows, new_bar_rows, FocusRender::ScrollRegion),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Truncate;
+
+    fn content(pinned_prompt: &str, is_ai_tool: bool, has_note: bool) -> BarContent<'_> {
+        BarContent {
+            pinned_prompt,
+            is_ai_tool,
+            has_note,
+            cols: 80,
+        }
+    }
 
     #[test]
     fn bar_rows_non_ai() {
-        assert_eq!(focus_bar_rows("anything", false), 2);
-        assert_eq!(focus_bar_rows("", false), 2);
+        assert_eq!(
+            focus_bar_rows(&content("anything", false, false), &BarConfig::default()),
+            2
+        );
+        assert_eq!(
+            focus_bar_rows(&content("", false, false), &BarConfig::default()),
+            2
+        );
     }
 
     #[test]
     fn bar_rows_ai_empty_pin() {
-        assert_eq!(focus_bar_rows("", true), 3);
+        assert_eq!(
+            focus_bar_rows(&content("", true, false), &BarConfig::default()),
+            3
+        );
     }
 
     #[test]
     fn bar_rows_ai_single_line() {
-        assert_eq!(focus_bar_rows("hello world", true), 3);
+        assert_eq!(
+            focus_bar_rows(&content("hello world", true, false), &BarConfig::default()),
+            3
+        );
     }
 
     #[test]
     fn bar_rows_ai_multiline() {
-        assert_eq!(focus_bar_rows("line1\nline2\nline3", true), 5);
+        assert_eq!(
+            focus_bar_rows(
+                &content("line1\nline2\nline3", true, false),
+                &BarConfig::default()
+            ),
+            5
+        );
+    }
+
+    #[test]
+    fn term_rows_by_render_mode() {
+        assert_eq!(term_rows(24, 3, FocusRender::ScrollRegion), 21);
+        assert_eq!(term_rows(24, 3, FocusRender::Overlay), 24);
+        assert_eq!(term_rows(2, 3, FocusRender::ScrollRegion), 1);
     }
 
     #[test]
     fn resize_no_change() {
-        let result = compute_bar_resize(40, 2, "", false);
+        let result = compute_bar_resize(40, 2, &content("", false, false), &BarConfig::default());
         assert!(result.is_none());
     }
 
     #[test]
     fn resize_grow() {
         // non-AI (2 rows) → AI with pin (3 rows)
-        let result = compute_bar_resize(40, 2, "prompt", true).unwrap();
+        let result = compute_bar_resize(
+            40,
+            2,
+            &content("prompt", true, false),
+            &BarConfig::default(),
+        )
+        .unwrap();
         assert_eq!(result.new_bar_rows, 3);
         assert_eq!(result.term_rows, 37);
         assert_eq!(result.clear_from, 38); // min(39, 38)
@@ -85,17 +150,90 @@ mod tests {
     #[test]
     fn resize_shrink() {
         // AI multiline (5 rows) → AI single line (3 rows)
-        let result = compute_bar_resize(40, 5, "single", true).unwrap();
+        let result = compute_bar_resize(
+            40,
+            5,
+            &content("single", true, false),
+            &BarConfig::default(),
+        )
+        .unwrap();
         assert_eq!(result.new_bar_rows, 3);
         assert_eq!(result.term_rows, 37);
         assert_eq!(result.clear_from, 36); // min(36, 38)
         assert_eq!(result.clear_to, 40);
     }
 
+    #[test]
+    fn bar_rows_without_separator() {
+        let bar = BarConfig {
+            separator: false,
+            ..BarConfig::default()
+        };
+        assert_eq!(focus_bar_rows(&content("", false, false), &bar), 1);
+        assert_eq!(focus_bar_rows(&content("prompt", true, false), &bar), 2);
+        assert_eq!(focus_bar_rows(&content("a\nb", true, false), &bar), 3);
+    }
+
+    #[test]
+    fn resize_separator_toggle() {
+        let bar = BarConfig {
+            separator: false,
+            ..BarConfig::default()
+        };
+        let result = compute_bar_resize(24, 2, &content("", false, false), &bar).unwrap();
+        assert_eq!(result.new_bar_rows, 1);
+        assert_eq!(result.term_rows, 23);
+    }
+
+    #[test]
+    fn bar_rows_with_note() {
+        let bar = BarConfig::default();
+        assert_eq!(focus_bar_rows(&content("", false, true), &bar), 3);
+        assert_eq!(focus_bar_rows(&content("a\nb", true, true), &bar), 5);
+    }
+
+    #[test]
+    fn bar_rows_cap_long_prompt() {
+        let prompt = vec!["line"; 50].join("\n");
+        let bar = BarConfig::default();
+        assert_eq!(focus_bar_rows(&content(&prompt, true, false), &bar), 6); // separator + 4 + hint
+        let resize = compute_bar_resize(24, 2, &content(&prompt, true, false), &bar).unwrap();
+        assert_eq!(resize.term_rows, 18);
+        // Even when the bars would fill the screen, the program keeps a row
+        let resize = compute_bar_resize(5, 2, &content(&prompt, true, false), &bar).unwrap();
+        assert_eq!(resize.term_rows, 1);
+    }
+
     #[test]
     fn resize_term_rows() {
-        let result = compute_bar_resize(24, 2, "a\nb", true).unwrap();
+        let result =
+            compute_bar_resize(24, 2, &content("a\nb", true, false), &BarConfig::default())
+                .unwrap();
         assert_eq!(result.new_bar_rows, 4);
         assert_eq!(result.term_rows, 20);
     }
+
+    #[test]
+    fn bar_rows_count_wrapped_prompt() {
+        let bar = BarConfig {
+            truncate: Truncate::Wrap,
+            ..BarConfig::default()
+        };
+        let prompt = "word ".repeat(40); // 200 columns
+        let wide = BarContent {
+            cols: 120,
+            ..content(&prompt, true, false)
+        };
+        assert_eq!(focus_bar_rows(&wide, &bar), 4); // separator + 2 + hint
+        assert_eq!(focus_bar_rows(&content(&prompt, true, false), &bar), 5);
+        let narrow = BarContent {
+            cols: 20,
+            ..content(&prompt, true, false)
+        };
+        assert_eq!(focus_bar_rows(&narrow, &bar), 6); // capped at max_pin_rows
+        assert_eq!(
+            focus_bar_rows(&content(&prompt, true, false), &BarConfig::default()),
+            3
+        );
+    }
 }
@@ -1,61 +1,188 @@
 mod app;
+mod cli;
+mod clipboard;
+mod config;
+mod control;
+mod hook;
 mod key;
 mod layout;
 mod session;
+mod state;
 mod ui;
 mod update;
 
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::sync::mpsc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use crossterm::event::{
-    Event, KeyCode, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
-    PushKeyboardEnhancementFlags,
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event,
+    KeyCode, KeyModifiers, KeyboardEnhancementFlags, MouseEvent, MouseEventKind,
+    PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
 };
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
-use app::App;
-use key::key_event_to_bytes;
-use layout::focus_bar_rows;
-use session::Session;
+use app::complete;
+use app::copy::{CopyMode, CopyOutcome};
+use app::input::{InputKind, InputOutcome, LineInput};
+use app::search::{self, Search};
+use app::{App, Split};
+use cli::Args;
+use config::{Config, FocusRender};
+use key::keymap::Action;
+use key::{
+    is_prefix_key, is_soft_newline, key_event_to_bytes, macro_bytes, paste_bytes, wheel_bytes,
+    PREFIX_BYTE,
+};
+use layout::{focus_bar_rows, term_rows, BarContent};
+use session::{
+    export_path, recording_path, tilde_path, AiActivity, ExportFormat, Session, SessionSpec,
+    SessionStatus, Signal,
+};
 use ui::ansi;
 use ui::bar::{self, BarState};
+use ui::split;
+
+/// Overlay mode: how long output must stay quiet before the bars are drawn again.
+const OVERLAY_IDLE: Duration = Duration::from_millis(150);
+
+/// How long an AI session's output must stay quiet before its turn counts as finished.
+const FINISHED_QUIET: Duration = Duration::from_secs(3);
+
+/// Rows one notch of the mouse wheel scrolls.
+const WHEEL_LINES: isize = 3;
+
+/// Everything drawn in a tick is buffered and reaches the terminal in one write.
+type Output = io::BufWriter<io::Stdout>;
+
+/// Heavy output spills in writes of this size, so the terminal keeps drawing
+/// while the rest of the tick is parsed.
+const OUTPUT_BUFFER: usize = 64 * 1024;
 
 fn main() -> Result<()> {
+    let args = Args::parse(std::env::args().skip(1))?;
+    let (mut config, config_error) = match Config::load() {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(format!("config: {e:#}"))),
+    };
+    args.apply_display_flags(&mut config);
+    let cwd = std::env::current_dir()?;
+    let home = config::home_dir();
+    let state_path = state::state_path(&home);
+    let no_args = args.paths.is_empty() && args.command.is_empty();
+    let mut startup_errors: Vec<String> = config_error.into_iter().collect();
+    let restored = if config.restore_sessions && no_args {
+        state::load(&state_path).unwrap_or_else(|e| {
+            startup_errors.push(format!("saved sessions: {e:#}"));
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+    let (mut specs, skipped) = if !restored.is_empty() {
+        cli::startup_specs(&restored, &cwd)
+    } else if no_args && !config.sessions.is_empty() {
+        cli::startup_specs(&config.sessions, &cwd)
+    } else {
+        args.session_specs(&cwd)
+    };
+    if specs.is_empty() {
+        specs.push(SessionSpec {
+            cwd,
+            command: args.command.clone(),
+            name: None,
+            env: HashMap::new(),
+        });
+    }
+
     enable_raw_mode()?;
 
-    let mut stdout = io::stdout();
-    let _ = crossterm::execute!(
-        stdout,
-        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-    );
-    let cwd = std::env::current_dir()?;
+    let mut stdout = Output::with_capacity(OUTPUT_BUFFER, io::stdout());
+    // Legacy terminals don't answer the query and keep the classic encodings
+    let keyboard_enhanced = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhanced {
+        let _ = crossterm::execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
+    }
+    // Pastes arrive whole, to be bracketed again for programs that want it
+    let _ = crossterm::execute!(stdout, EnableBracketedPaste);
     let (cols, rows) = crossterm::terminal::size()?;
 
-    let mut app = App::new(cwd, rows, cols)?;
+    let mut app = App::new(&specs, rows, cols, config)?;
+    app.args = args;
+    for message in startup_errors.into_iter().chain(skipped) {
+        app.push_error(message);
+    }
     let update_rx = update::check_for_update();
+    sync_control(&mut app);
 
     setup_focus_mode(&mut stdout, &mut app);
 
     loop {
+        app.frame += 1;
         poll_update(&mut app, &update_rx);
-        let idx = app.focus_idx;
+        poll_control(&mut stdout, &mut app);
+        let exited = app.poll_exits();
+        let idx = app.focus_idx();
+        if exited && app.overlay_shown {
+            refresh_hint_bar(&mut stdout, &app, idx);
+        }
         run_focus_tick(&mut stdout, &mut app, idx)?;
         if app.should_quit {
             break;
         }
     }
 
+    let quit_dir = app.sessions.get(app.focus_idx()).map(|s| s.current_dir());
+
     ansi::reset_scroll_region(&mut stdout);
     crossterm::execute!(stdout, crossterm::cursor::Show)?;
-    let _ = crossterm::execute!(stdout, PopKeyboardEnhancementFlags);
+    if app.config.mouse {
+        let _ = crossterm::execute!(stdout, DisableMouseCapture);
+    }
+    let _ = crossterm::execute!(stdout, DisableBracketedPaste);
+    if keyboard_enhanced {
+        let _ = crossterm::execute!(stdout, PopKeyboardEnhancementFlags);
+    }
     disable_raw_mode()?;
 
     ansi::clear_screen(&mut stdout);
     stdout.flush()?;
 
+    if app.config.restore_sessions {
+        let saved = state::saved_sessions(&app.sessions);
+        if let Err(e) = state::save(&state_path, &saved) {
+            eprintln!("murmur: saving sessions: {e:#}");
+        }
+    }
+
+    if let Some(shell) = &app.config.exec_on_quit {
+        // Sessions must be gone before this process is replaced
+        drop(app.sessions);
+        exec_shell(shell, quit_dir.as_deref())?;
+    }
+
+    Ok(())
+}
+
+/// Replace murmur with `shell` (after `$VAR`/`~` expansion), started in `dir`.
+#[cfg(unix)]
+fn exec_shell(shell: &str, dir: Option<&std::path::Path>) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    let program = shellexpand::full(shell)?;
+    let mut cmd = std::process::Command::new(program.as_ref());
+    if let Some(dir) = dir.filter(|d| d.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    Err(anyhow::anyhow!("exec {program}: {}", cmd.exec()))
+}
+
+#[cfg(not(unix))]
+fn exec_shell(_shell: &str, _dir: Option<&std::path::Path>) -> Result<()> {
     Ok(())
 }
 
@@ -69,12 +196,17 @@ fn poll_update(app: &mut App, rx: &mpsc::Receiver<Option<String>>) {
 }
 
 /// Forward a key event to the PTY session, recording PIN on Enter in AI mode.
-fn forward_key(session: &mut Session, key: &crossterm::event::KeyEvent) -> Result<()> {
-    if let Some(bytes) = key_event_to_bytes(key) {
-        if key.code == KeyCode::Enter
-            && !key.modifiers.contains(KeyModifiers::SHIFT)
-            && session.is_ai_tool()
-        {
+/// Shift/Alt+Enter send `soft_newline` instead and never submit.
+fn forward_key(
+    session: &mut Session,
+    key: &crossterm::event::KeyEvent,
+    soft_newline: &str,
+) -> Result<()> {
+    if is_soft_newline(key) {
+        return session.write_bytes(soft_newline.as_bytes());
+    }
+    if let Some(bytes) = key_event_to_bytes(key, session.kitty_keyboard()) {
+        if key.code == KeyCode::Enter && session.is_ai_tool() {
             session.record_pin();
         }
         session.write_bytes(&bytes)?;
@@ -82,134 +214,534 @@ fn forward_key(session: &mut Session, key: &crossterm::event::KeyEvent) -> Resul
     Ok(())
 }
 
+/// Paste `text` into the session, bracketed if its program asked for that.
+/// A pasted prompt shows up in the input box, where Enter pins it as usual.
+fn paste(session: &mut Session, text: &str) -> Result<()> {
+    let bracketed = session.screen().bracketed_paste();
+    session.write_bytes(&paste_bytes(text, bracketed))
+}
+
+/// Open or close the control socket to match `control_socket` in the config.
+fn sync_control(app: &mut App) {
+    if !app.config.control_socket {
+        app.control = None;
+        return;
+    }
+    if app.control.is_some() {
+        return;
+    }
+    let home = config::home_dir();
+    match control::Control::bind(&control::socket_path(&home)) {
+        Ok(control) => app.control = Some(control),
+        Err(e) => app.push_error(format!("control socket: {e}")),
+    }
+}
+
+/// Run the commands other programs sent over the control socket.
+fn poll_control(stdout: &mut Output, app: &mut App) {
+    let Some(mut control) = app.control.take() else {
+        return;
+    };
+    let idx = app.focus_idx();
+    let mut focused = false;
+    control.poll(|request| {
+        let target = app
+            .control_target(&request.session)
+            .ok_or_else(|| format!("no session named {}", request.session))?;
+        app.sessions[target]
+            .run_command(&request.cmd)
+            .map_err(|e| e.to_string())?;
+        focused |= target == idx;
+        Ok(())
+    });
+    app.control = Some(control);
+    if focused && app.split.is_none() {
+        // A pinned command changes the pin bar like a typed prompt
+        refresh_pin_bar(stdout, app, idx);
+    }
+}
+
 /// Render bars and restore cursor to the session's position.
-fn render_bars_and_restore_cursor(stdout: &mut io::Stdout, app: &App, idx: usize) {
+fn render_bars_and_restore_cursor(stdout: &mut Output, app: &App, idx: usize) {
     render_all_bars(stdout, app, idx);
     restore_session_cursor(stdout, app, idx);
 }
 
+/// Gather the bar state for a session from immutable borrows of App + Session.
+fn bar_state<'a>(app: &'a App, idx: usize, title: &'a str) -> BarState<'a> {
+    let session = &app.sessions[idx];
+    let ai_tool = session.ai_tool();
+    let is_ai = ai_tool.is_some();
+    let running = session.status() == SessionStatus::Running;
+    let (uptime, quiet) = session.runtime_labels();
+    BarState {
+        rows: app.rows,
+        cols: app.cols,
+        bar_rows: app.bar_rows,
+        is_ai,
+        pinned_prompt: session.pins.current(),
+        pin_position: session.pins.position(),
+        draft: (is_ai && app.config.bar.live_prompt)
+            .then(|| session.draft_prompt())
+            .flatten(),
+        note: session.note.as_deref(),
+        input: app.input.as_ref(),
+        prefix_armed: app.prefix_armed,
+        window_title: title,
+        update_version: app.update_available.as_deref(),
+        paused: session.is_paused(),
+        recording: session.is_recording(),
+        broadcast: app.broadcast,
+        message: app.error_message.as_deref(),
+        session_name: &session.name,
+        session_position: (app.sessions.len() > 1).then(|| (idx + 1, app.sessions.len())),
+        active_sessions: app
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|&(i, s)| i != idx && s.has_unseen_output())
+            .map(|(i, _)| i + 1)
+            .collect(),
+        cwd: app.config.bar.show_cwd.then(|| {
+            // Not home_dir(): with HOME unset, "" leaves paths alone where "/" would
+            // shorten every one of them
+            let home = std::env::var("HOME").unwrap_or_default();
+            tilde_path(&session.current_dir(), &home)
+        }),
+        ai_tool,
+        cwd_missing: !session.cwd_exists(),
+        starting: running && session.is_starting(),
+        exited: match session.status() {
+            SessionStatus::Exited(code) => Some(code),
+            SessionStatus::Running => None,
+        },
+        uptime: Some(uptime),
+        quiet,
+        scrolled: !session.is_live(),
+        copying: app.copy.is_some(),
+        search: app
+            .search
+            .as_ref()
+            .map(|search| (search.current + 1, search.matches.len())),
+        dropped: session.dropped_bytes() > 0,
+        frame: app.frame,
+        at_prompt: at_prompt(app, session, is_ai),
+        bar: &app.config.bar,
+        keymap: &app.config.keymap,
+        theme: &app.config.theme,
+        glyphs: &app.config.glyphs,
+    }
+}
+
+/// Whether the session waits for input: a shell at its prompt, or an AI tool
+/// judged by its busy/idle markers. `None` when it can't be told.
+fn at_prompt(app: &App, session: &Session, is_ai: bool) -> Option<bool> {
+    if session.status() != SessionStatus::Running || session.is_starting() {
+        return None;
+    }
+    let detect = &app.config.detect;
+    if !is_ai {
+        return Some(session.is_at_prompt(&detect.prompt_suffixes));
+    }
+    match session.ai_activity(&detect.ai_busy_markers, &detect.ai_idle_markers) {
+        AiActivity::Busy => Some(false),
+        AiActivity::Idle => Some(true),
+        AiActivity::Unknown => None,
+    }
+}
+
 /// Render bars using immutable borrows of App + Session.
-fn render_all_bars(stdout: &mut io::Stdout, app: &App, idx: usize) {
+fn render_all_bars(stdout: &mut Output, app: &App, idx: usize) {
     if let Some(session) = app.sessions.get(idx) {
         let title = session.window_title();
-        let state = BarState {
-            rows: app.rows,
-            cols: app.cols,
-            bar_rows: app.bar_rows,
-            is_ai: session.is_ai_tool(),
-            pinned_prompt: session.pins.current(),
-            pin_position: session.pins.position(),
-            prefix_armed: app.prefix_armed,
-            window_title: &title,
-            update_version: app.update_available.as_deref(),
-        };
-        bar::render_bars(stdout, &state);
+        bar::render_bars(stdout, &bar_state(app, idx, &title));
     }
 }
 
-fn restore_session_cursor(stdout: &mut io::Stdout, app: &App, idx: usize) {
+/// Render the separator and pin bar, leaving the hint bar untouched.
+fn render_pin_area(stdout: &mut Output, app: &App, idx: usize) {
     if let Some(session) = app.sessions.get(idx) {
-        let (cr, cc) = session.screen().cursor_position();
-        ansi::move_to(stdout, cr + 1, cc + 1);
+        let title = session.window_title();
+        bar::render_pin_area(stdout, &bar_state(app, idx, &title));
+    }
+}
+
+/// Put the cursor back where the program left it, or at the end of the hint bar input.
+fn restore_session_cursor(stdout: &mut Output, app: &App, idx: usize) {
+    if let Some(input) = &app.input {
+        ansi::move_to(stdout, app.rows, input.cursor_col());
+    } else if let Some(copy) = &app.copy {
+        ansi::move_to(stdout, copy.cursor.row + 1, copy.cursor.col + 1);
+    } else if let Some(session) = app.sessions.get(idx).filter(|s| s.is_live()) {
+        // Scrolled back, the cursor stays hidden; the program's position is off the view
+        if app.split.is_some() {
+            // Panes are drawn from the parser, so the wrap state doesn't matter there
+            let (cr, cc) = session.screen().cursor_position();
+            ansi::move_to(stdout, cr + 1, cc + 1 + pane_offset(app, idx));
+        } else {
+            bar::restore_cursor(stdout, session.screen());
+        }
     }
 }
 
 /// Set up Focus mode: clear screen, restore PTY contents, set scroll region, render bars.
-fn setup_focus_mode(stdout: &mut io::Stdout, app: &mut App) {
+fn setup_focus_mode(stdout: &mut Output, app: &mut App) {
     let rows = app.rows;
     let cols = app.cols;
-    let idx = app.focus_idx;
+    let idx = app.focus_idx();
+    let render = app.config.focus_render;
+    if app.config.mouse {
+        crossterm::queue!(stdout, EnableMouseCapture).ok();
+    }
+    if app.split.is_some() {
+        if app
+            .split_indices()
+            .is_some_and(|(left, right)| idx == left || idx == right)
+        {
+            setup_split(stdout, app);
+            stdout.flush().ok();
+            return;
+        }
+        app.split = None;
+    }
 
+    let mut resize_error = None;
     if let Some(session) = app.sessions.get_mut(idx) {
-        let is_ai = session.is_ai_tool();
-        app.bar_rows = focus_bar_rows(session.pins.current(), is_ai);
-        let bar_rows = app.bar_rows;
-
-        let term_rows = rows.saturating_sub(bar_rows);
-        let _ = session.resize(term_rows, cols);
+        app.bar_rows = focus_bar_rows(&bar_content(session, cols), &app.config.bar);
+        let term_rows = term_rows(rows, app.bar_rows, render);
+        // Already parsed; the screen is redrawn whole below
+        resize_error = session.resize(term_rows, cols).1.err();
 
         ansi::clear_screen(stdout);
 
-        let contents = session.screen().contents_formatted();
-        stdout.write_all(&contents).ok();
+        bar::restore_screen(stdout, session.screen());
 
-        if !session.screen().alternate_screen() {
-            ansi::set_scroll_region(stdout, 1, rows.saturating_sub(bar_rows));
+        // A full-screen program is already sized to stop above the bars; a region
+        // left from before it took over would make it scroll the wrong rows
+        if render == FocusRender::Overlay || session.screen().alternate_screen() {
+            ansi::reset_scroll_region(stdout);
+        } else {
+            ansi::set_scroll_region(stdout, 1, term_rows);
         }
     }
+    if let Some(e) = resize_error {
+        app.push_error(format!("resize failed: {e}"));
+    }
 
     render_bars_and_restore_cursor(stdout, app, idx);
+    app.overlay_shown = true;
     stdout.flush().ok();
 }
 
+/// Lay out the split view: both sessions resized to their panes above the bars
+/// and drawn from their parsers, since passthrough can't share the screen.
+fn setup_split(stdout: &mut Output, app: &mut App) {
+    let Some((left, right)) = app.split_indices() else {
+        return;
+    };
+    if let Some(session) = app.sessions.get(app.focus_idx()) {
+        app.bar_rows = focus_bar_rows(&bar_content(session, app.cols), &app.config.bar);
+    }
+    let rows = term_rows(app.rows, app.bar_rows, FocusRender::ScrollRegion);
+    let (left_cols, right_cols) = split::pane_widths(app.cols);
+    let mut errors = Vec::new();
+    for (i, cols) in [(left, left_cols), (right, right_cols)] {
+        // Queued output is parsed, and both panes are redrawn below
+        if let (_, Err(e)) = app.sessions[i].resize(rows, cols) {
+            errors.push(format!("resize failed: {e}"));
+        }
+    }
+    for message in errors {
+        app.push_error(message);
+    }
+    ansi::reset_scroll_region(stdout);
+    ansi::clear_screen(stdout);
+    app.overlay_shown = true;
+    draw_split(stdout, app);
+}
+
+/// Repaint both panes, the divider and the bars, leaving the cursor in the focused pane.
+fn draw_split(stdout: &mut Output, app: &App) {
+    let Some((left, right)) = app.split_indices() else {
+        return;
+    };
+    let (left_cols, _) = split::pane_widths(app.cols);
+    split::render_pane(stdout, app.sessions[left].screen(), 1);
+    split::render_pane(stdout, app.sessions[right].screen(), left_cols + 2);
+    let rows = term_rows(app.rows, app.bar_rows, FocusRender::ScrollRegion);
+    split::render_divider(
+        &mut ansi::SgrFilter::new(&mut *stdout, app.config.theme.color),
+        left_cols + 1,
+        rows,
+        &app.config.theme,
+        &app.config.glyphs,
+    );
+    let idx = app.focus_idx();
+    if app.sessions[idx].screen().hide_cursor() {
+        crossterm::queue!(stdout, crossterm::cursor::Hide).ok();
+    } else {
+        crossterm::queue!(stdout, crossterm::cursor::Show).ok();
+    }
+    render_bars_and_restore_cursor(stdout, app, idx);
+}
+
+/// Columns left of the session's pane: nonzero only for the right half of a split.
+fn pane_offset(app: &App, idx: usize) -> u16 {
+    match app.split_indices() {
+        Some((_, right)) if right == idx => split::pane_widths(app.cols).0 + 1,
+        _ => 0,
+    }
+}
+
+/// Show the focused session beside the next one, or go back to one session.
+fn toggle_split(stdout: &mut Output, app: &mut App) {
+    if app.split.take().is_none() {
+        let idx = app.focus_idx();
+        let other = app.neighbor_idx(true);
+        if other == idx {
+            app.push_error("split needs a second session".into());
+            refresh_hint_bar(stdout, app, idx);
+            return;
+        }
+        app.split = Some(Split {
+            left: app.sessions[idx].id,
+            right: app.sessions[other].id,
+        });
+    }
+    setup_focus_mode(stdout, app);
+}
+
+/// What decides the height of `session`'s bars at `cols` columns.
+fn bar_content(session: &Session, cols: u16) -> BarContent<'_> {
+    BarContent {
+        pinned_prompt: session.pins.current(),
+        is_ai_tool: session.is_ai_tool(),
+        has_note: session.note.is_some(),
+        cols,
+    }
+}
+
+/// Recompute the bar height for a session. In scroll-region mode a change clears the
+/// old bar rows, resizes the PTY and moves the scroll region; in overlay mode the program
+/// keeps its full height and only the rows under the old bars are repainted.
+/// Returns true when the height changed.
+fn sync_bar_rows(stdout: &mut Output, app: &mut App, idx: usize) -> bool {
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return false;
+    };
+    let content = bar_content(session, app.cols);
+    if app.split.is_some() {
+        // The split lays itself out again; nothing is left for the caller to repaint
+        if focus_bar_rows(&content, &app.config.bar) != app.bar_rows {
+            setup_split(stdout, app);
+        }
+        return false;
+    }
+    match app.config.focus_render {
+        FocusRender::Overlay => {
+            let new_bar_rows = focus_bar_rows(&content, &app.config.bar);
+            if new_bar_rows == app.bar_rows {
+                return false;
+            }
+            bar::clear_overlay(stdout, session.screen(), app.rows, app.bar_rows);
+            app.bar_rows = new_bar_rows;
+        }
+        FocusRender::ScrollRegion => {
+            let Some(resize) =
+                bar::apply_bar_resize(stdout, app.rows, app.bar_rows, &content, &app.config.bar)
+            else {
+                return false;
+            };
+            app.bar_rows = resize.new_bar_rows;
+            let (chunks, resized) = session.resize(resize.term_rows, app.cols);
+            if !session.screen().alternate_screen() {
+                ansi::set_scroll_region(stdout, 1, resize.term_rows);
+            }
+            forward_output(stdout, app, idx, &chunks).ok();
+            if let Err(e) = resized {
+                app.push_error(format!("resize failed: {e}"));
+            }
+        }
+    }
+    true
+}
+
+/// Write output of the focused session to the terminal, uncovering the program rows
+/// under an overlay first. While scrolled back or copying the view is frozen, and a
+/// split is drawn from the parsers; output then only reaches the parser.
+fn forward_output(
+    stdout: &mut Output,
+    app: &mut App,
+    idx: usize,
+    chunks: &[Vec<u8>],
+) -> io::Result<()> {
+    let Some(session) = app.sessions.get(idx) else {
+        return Ok(());
+    };
+    let frozen =
+        !session.is_live() || app.copy.is_some() || app.search.is_some() || app.split.is_some();
+    if chunks.is_empty() || frozen {
+        return Ok(());
+    }
+    if app.config.focus_render == FocusRender::Overlay {
+        bar::clear_overlay(stdout, session.screen(), app.rows, app.bar_rows);
+        bar::restore_cursor(stdout, session.screen());
+        app.overlay_shown = false;
+        app.last_output = Instant::now();
+    }
+    for chunk in chunks {
+        stdout.write_all(chunk)?;
+    }
+    Ok(())
+}
+
 /// One tick of the Focus mode loop.
-fn run_focus_tick(stdout: &mut io::Stdout, app: &mut App, idx: usize) -> Result<()> {
-    let rows = app.rows;
-    let cols = app.cols;
+fn run_focus_tick(stdout: &mut Output, app: &mut App, idx: usize) -> Result<()> {
+    let overlay = app.config.focus_render == FocusRender::Overlay;
 
-    // 1. Drain raw PTY output from the focused session
+    // 1. Drain raw PTY output, once per session; the focused session's is also forwarded
+    let mut pending: Vec<Vec<Vec<u8>>> = app
+        .sessions
+        .iter_mut()
+        .map(Session::drain_raw_chunks)
+        .collect();
+    let chunks = pending.get_mut(idx).map(std::mem::take).unwrap_or_default();
     let mut has_output = false;
     let mut is_alt = false;
+    let mut errors = Vec::new();
+    forward_output(stdout, app, idx, &chunks)?;
     if let Some(session) = app.sessions.get_mut(idx) {
-        let chunks = session.drain_raw_chunks();
         if !chunks.is_empty() {
             has_output = true;
-            for chunk in &chunks {
-                stdout.write_all(chunk)?;
-                session.feed_parser(chunk);
+            if let Err(e) = session.ingest_output(&chunks, true) {
+                errors.push(e.to_string());
             }
-            stdout.flush()?;
-
-            session.try_update_pin();
-
+            session.update_attention();
             is_alt = session.screen().alternate_screen();
-            let is_ai = session.is_ai_tool();
+        }
+    }
 
-            if let Some(resize) =
-                bar::apply_bar_resize(stdout, rows, app.bar_rows, session.pins.current(), is_ai)
-            {
-                app.bar_rows = resize.new_bar_rows;
-                let _ = session.resize(resize.term_rows, cols);
+    // Keep background sessions' screens current
+    let mut new_activity = false;
+    let other_pane = app
+        .split_indices()
+        .map(|(left, right)| if left == idx { right } else { left });
+    let mut pane_output = false;
+    for (i, (session, chunks)) in app.sessions.iter_mut().zip(&pending).enumerate() {
+        if i == idx || chunks.is_empty() {
+            continue;
+        }
+        let was_unseen = session.has_unseen_output();
+        if let Err(e) = session.ingest_output(chunks, false) {
+            errors.push(e.to_string());
+        }
+        if other_pane == Some(i) {
+            // On screen in the other pane, so already seen
+            session.mark_seen();
+            pane_output = true;
+        } else {
+            new_activity |= !was_unseen;
+        }
+        if session.update_attention() && app.config.notify.attention {
+            let body = format!("{} needs attention", session.name);
+            if let Err(e) = hook::desktop_notification("murmur", &body) {
+                errors.push(format!("notification: {e}"));
             }
         }
     }
+    for message in errors {
+        app.push_error(message);
+    }
+    // The focused session's transitions are consumed too, so switching away
+    // from a finished session doesn't report it later
+    for (i, session) in app.sessions.iter_mut().enumerate() {
+        if session.update_finished(FINISHED_QUIET) && i != idx && app.config.notify.finished {
+            ansi::bell_notify(stdout, &format!("{} finished", session.name));
+        }
+    }
+    // The busy spinner moves on its own while the program is silent
+    let spin = app.frame.is_multiple_of(ansi::SPINNER_STEP)
+        && app
+            .sessions
+            .get(idx)
+            .is_some_and(|s| at_prompt(app, s, s.ai_tool().is_some()) == Some(false));
+    if (new_activity || app.hint_changed(idx) || spin) && app.overlay_shown {
+        draw_hint_bar(stdout, app, idx);
+    }
 
-    if has_output {
-        if is_alt {
-            ansi::reset_scroll_region(stdout);
-        } else {
-            ansi::set_scroll_region(stdout, 1, rows.saturating_sub(app.bar_rows));
-            render_bars_and_restore_cursor(stdout, app, idx);
+    if app.split.is_some() {
+        if has_output || pane_output {
+            sync_bar_rows(stdout, app, idx);
+            draw_split(stdout, app);
         }
-        stdout.flush().ok();
+    } else if has_output {
+        sync_bar_rows(stdout, app, idx);
+        // In overlay mode the bars come back once output goes quiet
+        if !overlay {
+            if is_alt {
+                ansi::reset_scroll_region(stdout);
+            } else {
+                // From the current size, not the one in effect when the program entered
+                // the alternate screen: it may have been resized in between
+                let bottom = term_rows(app.rows, app.bar_rows, FocusRender::ScrollRegion);
+                ansi::set_scroll_region(stdout, 1, bottom);
+                render_bars_and_restore_cursor(stdout, app, idx);
+            }
+        }
+    } else if overlay && !app.overlay_shown && app.last_output.elapsed() >= OVERLAY_IDLE {
+        render_bars_and_restore_cursor(stdout, app, idx);
+        if let Some(session) = app.sessions.get(idx) {
+            stdout.write_all(&session.screen().attributes_formatted())?;
+        }
+        app.overlay_shown = true;
     }
+    stdout.flush()?;
 
     // 2. Poll for events
     if let Some(ev) = App::poll_event(Duration::from_millis(16))? {
         match ev {
             Event::Key(key) => {
+                if app.error_message.take().is_some() {
+                    refresh_hint_bar(stdout, app, idx);
+                }
                 handle_focus_key(stdout, app, key, idx)?;
             }
-            Event::Paste(text) => {
-                if let Some(session) = app.sessions.get_mut(idx) {
-                    session.write_bytes(text.as_bytes())?;
-                }
+            Event::Mouse(mouse) => handle_mouse(stdout, app, &mouse, idx)?,
+            Event::Paste(text) => app.send_input(idx, |session| paste(session, &text))?,
+            Event::Resize(new_cols, new_rows) if app.split.is_some() => {
+                app.rows = new_rows;
+                app.cols = new_cols;
+                setup_focus_mode(stdout, app);
             }
             Event::Resize(new_cols, new_rows) => {
                 app.rows = new_rows;
                 app.cols = new_cols;
+                // A wrapped pin takes more or fewer rows at the new width
+                if let Some(session) = app.sessions.get(idx) {
+                    app.bar_rows = focus_bar_rows(&bar_content(session, new_cols), &app.config.bar);
+                }
+                let term_rows = term_rows(new_rows, app.bar_rows, app.config.focus_render);
 
-                if let Some(session) = app.sessions.get_mut(idx) {
-                    let term_rows = new_rows.saturating_sub(app.bar_rows);
-                    let _ = session.resize(term_rows, new_cols);
+                if let Some((chunks, resized)) = app
+                    .sessions
+                    .get_mut(idx)
+                    .map(|s| s.resize(term_rows, new_cols))
+                {
+                    forward_output(stdout, app, idx, &chunks)?;
+                    if let Err(e) = resized {
+                        app.push_error(format!("resize failed: {e}"));
+                    }
                 }
 
-                if app
+                if overlay {
+                    // The program repaints at its new size; redraw the bars once it settles
+                    app.overlay_shown = false;
+                    app.last_output = Instant::now();
+                } else if app
                     .sessions
                     .get(idx)
                     .is_some_and(|s| !s.screen().alternate_screen())
                 {
-                    let term_rows = new_rows.saturating_sub(app.bar_rows);
                     ansi::set_scroll_region(stdout, 1, term_rows);
                     render_bars_and_restore_cursor(stdout, app, idx);
                 }
@@ -223,64 +755,37 @@ fn run_focus_tick(stdout: &mut io::Stdout, app: &mut App, idx: usize) -> Result<
 }
 
 /// Render the hint bar with current session info.
-fn refresh_hint_bar(stdout: &mut io::Stdout, app: &App, idx: usize) {
-    let title = app
-        .sessions
-        .get(idx)
-        .map(|s| s.window_title())
-        .unwrap_or_default();
-    ansi::save_cursor(stdout);
-    ansi::render_hint_bar(
-        stdout,
-        app.rows,
-        app.prefix_armed,
-        &title,
-        app.update_available.as_deref(),
-    );
-    ansi::restore_cursor(stdout);
+fn refresh_hint_bar(stdout: &mut Output, app: &App, idx: usize) {
+    draw_hint_bar(stdout, app, idx);
     stdout.flush().ok();
 }
 
-/// Re-render the pin bar, handling bar_rows changes.
-fn refresh_pin_bar(stdout: &mut io::Stdout, app: &mut App, idx: usize) {
-    if !app.sessions[idx].is_ai_tool() {
-        return;
+/// Draw the hint bar into the buffer, leaving the flush to the caller.
+fn draw_hint_bar(stdout: &mut Output, app: &App, idx: usize) {
+    if let Some(session) = app.sessions.get(idx) {
+        let title = session.window_title();
+        ansi::save_cursor(stdout);
+        bar::render_hint(stdout, &bar_state(app, idx, &title));
+        if app.input.is_some() {
+            restore_session_cursor(stdout, app, idx);
+        } else {
+            ansi::restore_cursor(stdout);
+        }
     }
-    let resized = if let Some(resize) = bar::apply_bar_resize(
-        stdout,
-        app.rows,
-        app.bar_rows,
-        app.sessions[idx].pins.current(),
-        true,
-    ) {
-        app.bar_rows = resize.new_bar_rows;
-        let _ = app.sessions[idx].resize(resize.term_rows, app.cols);
-        if !app.sessions[idx].screen().alternate_screen() {
-            ansi::set_scroll_region(stdout, 1, resize.term_rows);
-        }
-        true
-    } else {
-        false
-    };
+}
+
+/// Re-render the note and pin bar, handling bar_rows changes.
+fn refresh_pin_bar(stdout: &mut Output, app: &mut App, idx: usize) {
+    let resized = sync_bar_rows(stdout, app, idx);
 
     if resized {
         // Re-render session content to fill freed/updated rows,
         // then restore cursor (set_scroll_region moves it to (1,1) per VT100 spec)
-        let contents = app.sessions[idx].screen().contents_formatted();
-        stdout.write_all(&contents).ok();
+        bar::restore_screen(stdout, app.sessions[idx].screen());
         restore_session_cursor(stdout, app, idx);
     }
 
-    let session = &app.sessions[idx];
-    ansi::render_bar_area(
-        stdout,
-        app.rows,
-        app.bar_rows,
-        app.cols,
-        true,
-        session.pins.current(),
-        session.pins.position(),
-    );
+    render_pin_area(stdout, app, idx);
 
     if resized {
         refresh_hint_bar(stdout, app, idx);
@@ -289,7 +794,7 @@ fn refresh_pin_bar(stdout: &mut io::Stdout, app: &mut App, idx: usize) {
 }
 
 /// Move pin cursor forward (next) or backward (prev).
-fn navigate_pin(stdout: &mut io::Stdout, app: &mut App, idx: usize, forward: bool) {
+fn navigate_pin(stdout: &mut Output, app: &mut App, idx: usize, forward: bool) {
     if let Some(session) = app.sessions.get_mut(idx) {
         let changed = if forward {
             session.pins.next()
@@ -302,27 +807,435 @@ fn navigate_pin(stdout: &mut io::Stdout, app: &mut App, idx: usize, forward: boo
     }
 }
 
+/// Rows a PageUp/PageDown moves: the program's visible height less one row of context.
+fn scroll_page(app: &App) -> isize {
+    let rows = term_rows(app.rows, app.bar_rows, app.config.focus_render);
+    rows.saturating_sub(1).max(1) as isize
+}
+
+/// Scroll the focused session's view through its scrollback. Reaching the bottom
+/// returns to the live screen and puts the program's cursor back.
+fn scroll_view(stdout: &mut Output, app: &mut App, idx: usize, lines: isize) {
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return;
+    };
+    session.scroll_by(lines);
+    if session.is_live() {
+        setup_focus_mode(stdout, app);
+        return;
+    }
+    bar::restore_screen(stdout, session.screen());
+    crossterm::queue!(stdout, crossterm::cursor::Hide).ok();
+    render_all_bars(stdout, app, idx);
+    stdout.flush().ok();
+}
+
+/// Mouse wheel over the focused session. On the alternate screen the program gets
+/// the wheel: as mouse reports when it asked for them, otherwise as arrow keys the
+/// way terminals scroll pagers. Elsewhere the wheel scrolls back through output.
+fn handle_mouse(stdout: &mut Output, app: &mut App, mouse: &MouseEvent, idx: usize) -> Result<()> {
+    let up = match mouse.kind {
+        MouseEventKind::ScrollUp => true,
+        MouseEventKind::ScrollDown => false,
+        _ => return Ok(()),
+    };
+    if app.input.is_some() || app.copy.is_some() || app.search.is_some() || app.split.is_some() {
+        return Ok(());
+    }
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return Ok(());
+    };
+    let screen = session.screen();
+    if screen.alternate_screen() {
+        let bytes = if screen.mouse_protocol_mode() == vt100::MouseProtocolMode::None {
+            let arrow: &[u8] = if up { b"\x1b[A" } else { b"\x1b[B" };
+            arrow.repeat(WHEEL_LINES as usize)
+        } else {
+            let (rows, cols) = screen.size();
+            let row = mouse.row.min(rows - 1);
+            let col = mouse.column.min(cols - 1);
+            wheel_bytes(up, col, row, mouse.modifiers)
+        };
+        session.write_bytes(&bytes)?;
+    } else if up {
+        scroll_view(stdout, app, idx, WHEEL_LINES);
+    } else if !session.is_live() {
+        scroll_view(stdout, app, idx, -WHEEL_LINES);
+    }
+    Ok(())
+}
+
+/// Keys while the view is scrolled back. Returns true when the key was used;
+/// anything else returns to the live screen and is handled as usual.
+fn handle_scroll_key(
+    stdout: &mut Output,
+    app: &mut App,
+    key: &crossterm::event::KeyEvent,
+    idx: usize,
+) -> bool {
+    let lines = match key.code {
+        KeyCode::PageUp => scroll_page(app),
+        KeyCode::PageDown => -scroll_page(app),
+        KeyCode::Up => 1,
+        KeyCode::Down => -1,
+        KeyCode::Esc | KeyCode::Char('q') => {
+            scroll_view(stdout, app, idx, isize::MIN);
+            return true;
+        }
+        KeyCode::Char('v') => {
+            enter_copy_mode(stdout, app, idx);
+            return true;
+        }
+        _ => {
+            scroll_view(stdout, app, idx, isize::MIN);
+            return false;
+        }
+    };
+    scroll_view(stdout, app, idx, lines);
+    true
+}
+
+/// Freeze the focused session's current view and start copy mode on it.
+fn enter_copy_mode(stdout: &mut Output, app: &mut App, idx: usize) {
+    let Some(session) = app.sessions.get(idx) else {
+        return;
+    };
+    app.copy = Some(CopyMode::new(session.screen()));
+    redraw_copy_view(stdout, app, idx);
+}
+
+fn redraw_copy_view(stdout: &mut Output, app: &App, idx: usize) {
+    let Some(copy) = &app.copy else {
+        return;
+    };
+    bar::render_copy_view(stdout, copy);
+    crossterm::queue!(stdout, crossterm::cursor::Show).ok();
+    render_bars_and_restore_cursor(stdout, app, idx);
+    stdout.flush().ok();
+}
+
+/// Keys in copy mode: move, select, and copy to the clipboard and paste buffer.
+fn handle_copy_key(
+    stdout: &mut Output,
+    app: &mut App,
+    key: &crossterm::event::KeyEvent,
+    idx: usize,
+) {
+    let Some(copy) = app.copy.as_mut() else {
+        return;
+    };
+    match copy.handle_key(key) {
+        CopyOutcome::Moved => {
+            redraw_copy_view(stdout, app, idx);
+            return;
+        }
+        CopyOutcome::Yank => {
+            let text = copy.selected_text();
+            if !text.is_empty() {
+                if let Err(e) = clipboard::copy(stdout, app.config.clipboard.backend, &text) {
+                    app.push_error(format!("clipboard: {e}"));
+                }
+                app.paste_buffer = Some(text);
+            }
+        }
+        CopyOutcome::Exit => {}
+    }
+    // Back to the scrolled view, or the live screen when not scrolled
+    app.copy = None;
+    scroll_view(stdout, app, idx, 0);
+}
+
+/// Seconds since the epoch, naming exports and recordings.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Save a session's scrollback and screen under `~/.murmur`, reporting the
+/// file (or the failure) in the hint bar.
+fn export_session(app: &mut App, idx: usize, format: ExportFormat) {
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return;
+    };
+    let home = config::home_dir();
+    let timestamp = unix_time();
+    let path = export_path(&home, &session.name, timestamp);
+    let message = match session.export(&path, format) {
+        Ok(()) => format!("saved {}", path.display()),
+        Err(e) => format!("export failed: {e}"),
+    };
+    app.push_error(message);
+}
+
+/// Start recording a session to `~/.murmur/<name>-<time>.cast`, or stop and
+/// report the file.
+fn toggle_recording(app: &mut App, idx: usize) {
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return;
+    };
+    if let Some(path) = session.stop_recording() {
+        app.push_error(format!("saved {}", path.display()));
+        return;
+    }
+    let home = config::home_dir();
+    let timestamp = unix_time();
+    let path = recording_path(&home, &session.name, timestamp);
+    if let Err(e) = session.start_recording(&path, timestamp) {
+        app.push_error(format!("recording failed: {e}"));
+    }
+}
+
+/// Find `query` in the focused session's scrollback and screen and show the
+/// newest match. Returns false, with a message, when nothing matched.
+fn start_search(
+    stdout: &mut Output,
+    app: &mut App,
+    idx: usize,
+    query: &str,
+    case_sensitive: bool,
+) -> bool {
+    let Some(session) = app.sessions.get_mut(idx) else {
+        return false;
+    };
+    let matches = search::find_matches(&session.all_lines(), query, case_sensitive);
+    if matches.is_empty() {
+        if !query.is_empty() {
+            app.push_error(format!("not found: {query}"));
+        }
+        return false;
+    }
+    app.search = Some(Search::new(matches));
+    show_search_match(stdout, app, idx);
+    true
+}
+
+/// Scroll to the current search match and highlight it.
+fn show_search_match(stdout: &mut Output, app: &mut App, idx: usize) {
+    let (Some(m), Some(session)) = (
+        app.search.as_ref().and_then(Search::current),
+        app.sessions.get_mut(idx),
+    ) else {
+        return;
+    };
+    let row = session.scroll_to_line(m.line);
+    bar::render_search_view(stdout, session.screen(), row, &m);
+    crossterm::queue!(stdout, crossterm::cursor::Hide).ok();
+    render_all_bars(stdout, app, idx);
+    stdout.flush().ok();
+}
+
+/// Keys while stepping through search results. Returns true when the key was
+/// used; anything else ends the search and is handled as usual.
+fn handle_search_key(
+    stdout: &mut Output,
+    app: &mut App,
+    key: &crossterm::event::KeyEvent,
+    idx: usize,
+) -> bool {
+    let Some(search) = app.search.as_mut() else {
+        return false;
+    };
+    match key.code {
+        KeyCode::Char('n') => search.next(),
+        KeyCode::Char('N') => search.prev(),
+        code => {
+            app.search = None;
+            scroll_view(stdout, app, idx, isize::MIN);
+            return matches!(code, KeyCode::Esc | KeyCode::Char('q'));
+        }
+    }
+    show_search_match(stdout, app, idx);
+    true
+}
+
+/// Route a key to the hint bar input, applying the text when it is submitted.
+fn handle_input_key(
+    stdout: &mut Output,
+    app: &mut App,
+    key: crossterm::event::KeyEvent,
+    idx: usize,
+) {
+    let Some(input) = app.input.as_mut() else {
+        return;
+    };
+    if input.kind == InputKind::NewSession && key.code == KeyCode::Tab {
+        complete_new_session(stdout, app, idx);
+        return;
+    }
+    if matches!(input.kind, InputKind::Close | InputKind::Quit) {
+        let kind = input.kind;
+        app.input = None;
+        if matches!(key.code, KeyCode::Char('y' | 'Y')) {
+            if kind == InputKind::Quit || !app.close_session(idx) {
+                app.should_quit = true;
+                return;
+            }
+            setup_focus_mode(stdout, app);
+            return;
+        }
+        refresh_hint_bar(stdout, app, idx);
+        restore_session_cursor(stdout, app, idx);
+        stdout.flush().ok();
+        return;
+    }
+    match input.handle_key(&key) {
+        InputOutcome::Editing => {}
+        InputOutcome::Cancel => app.input = None,
+        InputOutcome::Submit => {
+            let Some(input) = app.input.take() else {
+                return;
+            };
+            let text = input.buffer.trim().to_string();
+            match input.kind {
+                InputKind::Note => {
+                    if let Some(session) = app.sessions.get_mut(idx) {
+                        session.note = (!text.is_empty()).then_some(text);
+                    }
+                    refresh_pin_bar(stdout, app, idx);
+                }
+                InputKind::Rename => app.rename_session(idx, &text),
+                InputKind::NewSession => {
+                    open_sessions(stdout, app, &text);
+                    return;
+                }
+                InputKind::FindSession if !text.is_empty() => match app.find_session(&text) {
+                    Some(found) if found != idx => {
+                        enter_focus(stdout, app, found);
+                        return;
+                    }
+                    Some(_) => {}
+                    None => app.push_error(format!("no session matches {text}")),
+                },
+                InputKind::FindSession => {}
+                InputKind::Close | InputKind::Quit => {}
+                InputKind::Search { case_sensitive } => {
+                    if start_search(stdout, app, idx, &text, case_sensitive) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+    refresh_hint_bar(stdout, app, idx);
+    if app.input.is_none() {
+        restore_session_cursor(stdout, app, idx);
+        stdout.flush().ok();
+    }
+}
+
+/// Complete the directory at the end of the new-session prompt, listing the
+/// candidates after the text while several match.
+fn complete_new_session(stdout: &mut Output, app: &mut App, idx: usize) {
+    let cwd = app.new_session_dir();
+    let Some(input) = app.input.as_mut() else {
+        return;
+    };
+    match complete::complete_dir(&input.buffer, &cwd) {
+        Some(completion) => {
+            input.buffer = completion.line;
+            input.hint =
+                (!completion.candidates.is_empty()).then(|| completion.candidates.join("  "));
+        }
+        None => input.hint = Some("no matching directory".into()),
+    }
+    draw_hint_bar(stdout, app, idx);
+    restore_session_cursor(stdout, app, idx);
+    stdout.flush().ok();
+}
+
+/// Spawn the sessions described by the new-session prompt and focus the last one.
+fn open_sessions(stdout: &mut Output, app: &mut App, line: &str) {
+    let (specs, skipped) = match cli::prompt_specs(line, &app.new_session_dir()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            app.push_error(e.to_string());
+            (Vec::new(), Vec::new())
+        }
+    };
+    for message in skipped {
+        app.push_error(message);
+    }
+    for spec in &specs {
+        match app.add_session(spec) {
+            Ok(idx) => {
+                app.focus(idx);
+            }
+            Err(e) => app.push_error(format!("{}: {e}", spec.cwd.display())),
+        }
+    }
+    setup_focus_mode(stdout, app);
+}
+
+/// Switch Focus mode to session `idx`. The session left behind goes back to its
+/// live screen.
+fn enter_focus(stdout: &mut Output, app: &mut App, idx: usize) {
+    let current = app.focus_idx();
+    if let Some(session) = app.sessions.get_mut(current) {
+        session.scroll_by(isize::MIN);
+    }
+    if app.focus(idx) {
+        setup_focus_mode(stdout, app);
+    }
+}
+
 /// Handle a key event in Focus mode.
 fn handle_focus_key(
-    stdout: &mut io::Stdout,
+    stdout: &mut Output,
     app: &mut App,
     key: crossterm::event::KeyEvent,
     idx: usize,
 ) -> Result<()> {
-    let is_prefix = key.modifiers.contains(KeyModifiers::CONTROL)
-        && (key.code == KeyCode::Char('4') || key.code == KeyCode::Char('\\'));
+    if app.input.is_some() {
+        handle_input_key(stdout, app, key, idx);
+        return Ok(());
+    }
 
-    if is_prefix {
-        app.prefix_armed = true;
+    if app.copy.is_some() {
+        handle_copy_key(stdout, app, &key, idx);
+        return Ok(());
+    }
+
+    if app.search.is_some() && handle_search_key(stdout, app, &key, idx) {
+        return Ok(());
+    }
+
+    if app.sessions.get(idx).is_some_and(|s| !s.is_live())
+        && handle_scroll_key(stdout, app, &key, idx)
+    {
+        return Ok(());
+    }
+
+    if is_prefix_key(&key) {
+        if app.prefix_armed {
+            // Prefix twice sends a literal Ctrl+\ (e.g. SIGQUIT), like tmux's send-prefix.
+            // Only to the focused session, even while broadcasting.
+            app.prefix_armed = false;
+            if let Some(session) = app.sessions.get_mut(idx) {
+                session.write_bytes(&[PREFIX_BYTE])?;
+            }
+        } else {
+            app.prefix_armed = true;
+        }
         refresh_hint_bar(stdout, app, idx);
         return Ok(());
     }
 
     if app.prefix_armed {
         app.prefix_armed = false;
+        let action = app.config.keymap.action(key.code);
 
-        match key.code {
-            KeyCode::Char('x') => {
+        let full_screen = matches!(
+            action,
+            Some(Action::ScrollUp | Action::ScrollDown | Action::Copy | Action::Search)
+        );
+        if full_screen && app.split.is_some() {
+            // Scrollback, copy mode and search draw over the whole screen
+            toggle_split(stdout, app);
+        }
+
+        match action {
+            Some(Action::Unpin) => {
                 if let Some(session) = app.sessions.get_mut(idx) {
                     session.pins.delete();
                     if session.is_ai_tool() {
@@ -330,32 +1243,203 @@ fn handle_focus_key(
                     }
                 }
                 refresh_hint_bar(stdout, app, idx);
-                return Ok(());
             }
-            KeyCode::Char('u') => {
+            Some(Action::Pin) => {
+                let pinned = app.sessions.get_mut(idx).is_some_and(|s| s.pin_draft());
+                if pinned {
+                    refresh_pin_bar(stdout, app, idx);
+                } else {
+                    app.push_error("nothing to pin".into());
+                }
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::PrevPin | Action::NextPin) => {
+                navigate_pin(stdout, app, idx, action == Some(Action::NextPin));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Note) => {
+                let note = app
+                    .sessions
+                    .get(idx)
+                    .and_then(|s| s.note.as_deref())
+                    .unwrap_or("");
+                app.input = Some(LineInput::new(InputKind::Note, note));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::NewSession) => {
+                app.input = Some(LineInput::new(InputKind::NewSession, ""));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Close) => {
+                if let Some(session) = app.sessions.get(idx) {
+                    let question = if session.status() == SessionStatus::Running {
+                        format!("{:?} (its process will be killed)? [y/N]", session.name)
+                    } else {
+                        format!("{:?}? [y/N]", session.name)
+                    };
+                    app.input = Some(LineInput::new(InputKind::Close, &question));
+                }
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Interrupt) => {
+                if !app
+                    .sessions
+                    .get(idx)
+                    .is_some_and(|s| s.send_signal(Signal::Interrupt))
+                {
+                    app.push_error("nothing to interrupt".into());
+                }
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Record) => {
+                toggle_recording(app, idx);
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Broadcast) => {
+                app.broadcast = !app.broadcast;
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Reload) => match Config::load() {
+                Ok(config) => {
+                    let had_mouse = app.config.mouse;
+                    app.apply_config(config);
+                    sync_control(app);
+                    if had_mouse && !app.config.mouse {
+                        crossterm::queue!(stdout, DisableMouseCapture).ok();
+                    }
+                    app.push_error("config reloaded".into());
+                    setup_focus_mode(stdout, app);
+                }
+                Err(e) => {
+                    app.push_error(format!("config: {e:#}"));
+                    refresh_hint_bar(stdout, app, idx);
+                }
+            },
+            Some(Action::FindSession) => {
+                app.input = Some(LineInput::new(InputKind::FindSession, ""));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Rename) => {
+                let name = app.sessions.get(idx).map_or("", |s| s.name.as_str());
+                app.input = Some(LineInput::new(InputKind::Rename, name));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Update) => {
                 if let Some(ver) = &app.update_available {
                     ansi::save_cursor(stdout);
-                    ansi::render_update_message(stdout, app.rows, ver);
+                    let w = &mut ansi::SgrFilter::new(stdout, app.config.theme.color);
+                    ansi::render_update_message(w, app.rows, ver, &app.config.theme);
                     ansi::restore_cursor(stdout);
                     stdout.flush().ok();
                 } else {
                     refresh_hint_bar(stdout, app, idx);
                 }
-                return Ok(());
             }
-            KeyCode::Char('q') => {
-                app.should_quit = true;
-                return Ok(());
+            Some(Action::Info) => {
+                if let Some(session) = app.sessions.get(idx) {
+                    ansi::save_cursor(stdout);
+                    let w = &mut ansi::SgrFilter::new(stdout, app.config.theme.color);
+                    ansi::render_session_info(
+                        w,
+                        app.rows,
+                        session.pid(),
+                        session.project_path(),
+                        &app.config.theme,
+                        &app.config.glyphs,
+                    );
+                    ansi::restore_cursor(stdout);
+                    stdout.flush().ok();
+                }
             }
-            _ => {
+            Some(Action::Pause) => {
                 if let Some(session) = app.sessions.get_mut(idx) {
-                    session.write_bytes(&[0x1c])?;
-                    forward_key(session, &key)?;
+                    session.toggle_pause();
                 }
                 refresh_hint_bar(stdout, app, idx);
-                return Ok(());
             }
+            Some(Action::Quit) => match app.quit_question() {
+                Some(question) => {
+                    app.input = Some(LineInput::new(InputKind::Quit, &question));
+                    refresh_hint_bar(stdout, app, idx);
+                }
+                None => app.should_quit = true,
+            },
+            Some(Action::ScrollUp) => scroll_view(stdout, app, idx, scroll_page(app)),
+            Some(Action::ScrollDown) => scroll_view(stdout, app, idx, -scroll_page(app)),
+            Some(Action::Copy) => enter_copy_mode(stdout, app, idx),
+            Some(Action::Export | Action::ExportAnsi) => {
+                let format = if action == Some(Action::ExportAnsi) {
+                    ExportFormat::Ansi
+                } else {
+                    ExportFormat::PlainText
+                };
+                export_session(app, idx, format);
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Search) => {
+                let kind = InputKind::Search {
+                    case_sensitive: false,
+                };
+                app.input = Some(LineInput::new(kind, ""));
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Paste) => {
+                if let Some(text) = app.paste_buffer.clone() {
+                    app.send_input(idx, |session| paste(session, &text))?;
+                }
+                refresh_hint_bar(stdout, app, idx);
+            }
+            Some(Action::Split) => toggle_split(stdout, app),
+            Some(Action::OtherPane) => match app.split_indices() {
+                Some((left, right)) => {
+                    enter_focus(stdout, app, if left == idx { right } else { left });
+                }
+                None => refresh_hint_bar(stdout, app, idx),
+            },
+            Some(Action::MoveLeft | Action::MoveRight) => {
+                app.move_focused(action == Some(Action::MoveRight));
+                refresh_hint_bar(stdout, app, app.focus_idx());
+            }
+            Some(Action::NextSession | Action::PrevSession) => {
+                let next = app.neighbor_idx(action == Some(Action::NextSession));
+                if next == idx {
+                    refresh_hint_bar(stdout, app, idx);
+                } else {
+                    enter_focus(stdout, app, next);
+                }
+            }
+            None => match key.code {
+                KeyCode::Char(c @ '1'..='9') => {
+                    let target = c as usize - '1' as usize;
+                    if target < app.sessions.len() && target != idx {
+                        enter_focus(stdout, app, target);
+                    } else {
+                        refresh_hint_bar(stdout, app, idx);
+                    }
+                }
+                _ => {
+                    let macro_def = match key.code {
+                        KeyCode::Char(c) => app.config.macros.get(&c.to_string()),
+                        _ => None,
+                    };
+                    match macro_def.map(|def| macro_bytes(def)) {
+                        Some(bytes) => {
+                            app.send_input(idx, |session| session.write_bytes(&bytes))?
+                        }
+                        // An unbound key goes through with the prefix, to the focused
+                        // session alone since the prefix byte is SIGQUIT
+                        None => {
+                            if let Some(session) = app.sessions.get_mut(idx) {
+                                session.write_bytes(&[PREFIX_BYTE])?;
+                                forward_key(session, &key, &app.config.keys.soft_newline)?;
+                            }
+                        }
+                    }
+                    refresh_hint_bar(stdout, app, idx);
+                }
+            },
         }
+        return Ok(());
     }
 
     // Direct pin navigation: Ctrl+[ (prev) / Ctrl+] (next)
@@ -374,49 +1458,19 @@ fn handle_focus_key(
     }
 
     // Normal key → forward to PTY
-    let mut enter_resized = false;
-    if let Some(session) = app.sessions.get_mut(idx) {
-        forward_key(session, &key)?;
-
-        if key.code == KeyCode::Enter {
-            if let Some(resize) = bar::apply_bar_resize(
-                stdout,
-                app.rows,
-                app.bar_rows,
-                session.pins.current(),
-                session.is_ai_tool(),
-            ) {
-                app.bar_rows = resize.new_bar_rows;
-                session.resize(resize.term_rows, app.cols)?;
-                if !session.screen().alternate_screen() {
-                    ansi::set_scroll_region(stdout, 1, resize.term_rows);
-                }
-                enter_resized = true;
-            }
-        }
-    }
+    let soft_newline = app.config.keys.soft_newline.clone();
+    app.send_input(idx, |session| forward_key(session, &key, &soft_newline))?;
+    let enter_resized = key.code == KeyCode::Enter && sync_bar_rows(stdout, app, idx);
 
     if key.code == KeyCode::Enter {
         if enter_resized {
             if let Some(session) = app.sessions.get(idx) {
-                stdout
-                    .write_all(&session.screen().contents_formatted())
-                    .ok();
+                bar::restore_screen(stdout, session.screen());
             }
             restore_session_cursor(stdout, app, idx);
             refresh_hint_bar(stdout, app, idx);
         }
-        if let Some(session) = app.sessions.get(idx) {
-            ansi::render_bar_area(
-                stdout,
-                app.rows,
-                app.bar_rows,
-                app.cols,
-                session.is_ai_tool(),
-                session.pins.current(),
-                session.pins.position(),
-            );
-        }
+        render_pin_area(stdout, app, idx);
         stdout.flush().ok();
     }
 
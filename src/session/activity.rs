@@ -0,0 +1,74 @@
+/// What an AI tool appears to be doing, judging by its screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiActivity {
+    Busy,
+    Idle,
+    Unknown,
+}
+
+/// Rows at the bottom of the screen searched for markers; tools keep their
+/// status line near the input box.
+const ACTIVITY_ROWS: usize = 15;
+
+/// Busy when one of `busy` shows in the bottom rows (e.g. "esc to interrupt"),
+/// idle when one of `idle` does, unknown otherwise. Matching ignores case, and
+/// busy markers win.
+pub fn ai_activity(screen: &vt100::Screen, busy: &[String], idle: &[String]) -> AiActivity {
+    let (rows, cols) = screen.size();
+    let skip = (rows as usize).saturating_sub(ACTIVITY_ROWS);
+    let text = screen
+        .rows(0, cols)
+        .skip(skip)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase();
+    let shows = |markers: &[String]| {
+        markers
+            .iter()
+            .any(|m| !m.is_empty() && text.contains(&m.to_lowercase()))
+    };
+    if shows(busy) {
+        AiActivity::Busy
+    } else if shows(idle) {
+        AiActivity::Idle
+    } else {
+        AiActivity::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(rows: u16, input: &str) -> AiActivity {
+        let mut parser = vt100::Parser::new(rows, 60, 0);
+        parser.process(input.as_bytes());
+        ai_activity(
+            parser.screen(),
+            &["esc to interrupt".to_string()],
+            &["? for shortcuts".to_string()],
+        )
+    }
+
+    #[test]
+    fn busy_marker() {
+        let s = "\u{2736} Thinking\u{2026} (3s \u{b7} Esc to interrupt)\r\n> \r\n  ? for shortcuts";
+        assert_eq!(activity(10, s), AiActivity::Busy);
+    }
+
+    #[test]
+    fn idle_marker() {
+        assert_eq!(activity(10, "> \r\n  ? for shortcuts"), AiActivity::Idle);
+    }
+
+    #[test]
+    fn unknown_without_markers() {
+        assert_eq!(activity(10, "hello"), AiActivity::Unknown);
+    }
+
+    #[test]
+    fn markers_above_the_bottom_rows_are_ignored() {
+        let s = format!("esc to interrupt{}", "\r\n".repeat(20));
+        assert_eq!(activity(30, &s), AiActivity::Unknown);
+    }
+}
@@ -3,8 +3,9 @@ use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
 
 fn history_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".claude").join("history.jsonl")
+    crate::config::home_dir()
+        .join(".claude")
+        .join("history.jsonl")
 }
 
 /// Read the latest matching prompt from `~/.claude/history.jsonl`.
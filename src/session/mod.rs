@@ -1,46 +1,198 @@
+mod activity;
 mod history;
 mod pin;
 mod proc_name;
 mod prompt;
+mod record;
 mod selection;
+mod shell;
 
+pub use activity::AiActivity;
 pub use pin::PinHistory;
 
+use record::Recording;
+
+use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, TrySendError};
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use portable_pty::{CommandBuilder, MasterPty, PtySize};
+use serde::Deserialize;
 
-struct TitleTracker {
+/// What vt100 doesn't keep itself: the window title, and the kitty keyboard
+/// flags the program pushed (`CSI > flags u`, popped with `CSI < n u`).
+struct Tracker {
     title: Arc<Mutex<String>>,
+    kitty_flags: Arc<Mutex<Vec<u16>>>,
 }
 
-impl vt100::Callbacks for TitleTracker {
+impl vt100::Callbacks for Tracker {
     fn set_window_title(&mut self, _: &mut vt100::Screen, title: &[u8]) {
         if let Ok(mut t) = self.title.lock() {
             *t = String::from_utf8_lossy(title).to_string();
         }
     }
+
+    fn unhandled_csi(
+        &mut self,
+        _: &mut vt100::Screen,
+        i1: Option<u8>,
+        _i2: Option<u8>,
+        params: &[&[u16]],
+        c: char,
+    ) {
+        if c != 'u' {
+            return;
+        }
+        let Ok(mut stack) = self.kitty_flags.lock() else {
+            return;
+        };
+        let param = |i: usize| params.get(i).and_then(|p| p.first()).copied();
+        match i1 {
+            // Bounded like kitty's own stack, so a runaway program can't grow it
+            Some(b'>') if stack.len() < 256 => stack.push(param(0).unwrap_or(0)),
+            Some(b'<') => {
+                let n = param(0).unwrap_or(1).max(1) as usize;
+                let keep = stack.len().saturating_sub(n);
+                stack.truncate(keep);
+            }
+            Some(b'=') => {
+                let flags = param(0).unwrap_or(0);
+                if stack.is_empty() {
+                    stack.push(0);
+                }
+                if let Some(top) = stack.last_mut() {
+                    match param(1).unwrap_or(1) {
+                        2 => *top |= flags,
+                        3 => *top &= !flags,
+                        _ => *top = flags,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Where and what to launch for a new session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSpec {
+    pub cwd: PathBuf,
+    /// Program and arguments; empty runs `$SHELL`.
+    pub command: Vec<String>,
+    /// Fixed session name instead of the directory's.
+    pub name: Option<String>,
+    /// Extra environment variables for the program.
+    pub env: HashMap<String, String>,
+}
+
+/// Whether the session's child process is still alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionStatus {
+    Running,
+    /// Exit code; a child killed by a signal reports 1.
+    Exited(u32),
+}
+
+/// How `Session::export` writes a session's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    PlainText,
+    /// Keeps colors and attributes as escape sequences, for `less -R` or `cat`.
+    Ansi,
 }
 
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Chunks (up to 4 KiB each) buffered between the reader thread and the UI.
+/// Output beyond this is dropped and counted rather than growing without bound.
+const PTY_CHANNEL_CAPACITY: usize = 1024;
+
 pub struct Session {
+    /// Unique for the life of the process; unlike the index into
+    /// `App::sessions`, it never shifts.
+    pub id: u64,
+    pub name: String,
+    /// Set when the user renamed the session, so directory-based naming leaves it alone.
+    pub custom_name: bool,
     pub pins: PinHistory,
+    /// Sticky note set by the user, shown above the pin bar until cleared.
+    pub note: Option<String>,
     pin_pending_ts: Option<u128>,
     project_path: String,
+    pid: Option<u32>,
+    paused: bool,
+    /// Set once the child has written anything.
+    started: bool,
     window_title: Arc<Mutex<String>>,
-    parser: vt100::Parser<TitleTracker>,
+    kitty_flags: Arc<Mutex<Vec<u16>>>,
+    parser: vt100::Parser<Tracker>,
     pty_rx: mpsc::Receiver<Vec<u8>>,
+    /// Bytes the reader thread discarded because the channel was full.
+    dropped: Arc<AtomicU64>,
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
-    _child: Box<dyn portable_pty::Child + Send + Sync>,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    status: SessionStatus,
+    /// Last result of `needs_attention`, to notice transitions.
+    attention: bool,
+    /// Output arrived in the background since the session was last focused.
+    unseen_output: bool,
+    last_output: Option<Instant>,
+    spawned_at: Instant,
+    exited_at: Option<Instant>,
+    /// Output arrived since `update_finished` last saw the session go quiet.
+    working: bool,
+    ai_tools: Vec<AiTool>,
+    /// What the session was spawned with; empty for `$SHELL` or a raw PTY.
+    command: Vec<String>,
+    env: HashMap<String, String>,
+    recording: Option<Recording>,
 }
 
 impl Session {
-    pub fn spawn(cwd: PathBuf, rows: u16, cols: u16) -> Result<Self> {
+    /// Spawn a session from a spec, running `$SHELL` when no command is given.
+    pub fn spawn_spec(spec: &SessionSpec, rows: u16, cols: u16) -> Result<Self> {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".into());
+        let (program, args) = match spec.command.split_first() {
+            Some((program, args)) => (program.as_str(), args),
+            None => (shell.as_str(), &[][..]),
+        };
+        let mut session =
+            Self::spawn_with_env(spec.cwd.clone(), rows, cols, program, args, &spec.env)?;
+        if let Some(name) = &spec.name {
+            session.name = name.clone();
+            session.custom_name = true;
+        }
+        session.command = spec.command.clone();
+        session.env = spec.env.clone();
+        Ok(session)
+    }
+
+    #[cfg(test)]
+    pub fn spawn_with_command(
+        cwd: PathBuf,
+        rows: u16,
+        cols: u16,
+        program: &str,
+        args: &[String],
+    ) -> Result<Self> {
+        Self::spawn_with_env(cwd, rows, cols, program, args, &HashMap::new())
+    }
+
+    fn spawn_with_env(
+        cwd: PathBuf,
+        rows: u16,
+        cols: u16,
+        program: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
         let pty_system = portable_pty::native_pty_system();
         let pair = pty_system.openpty(PtySize {
             rows,
@@ -49,57 +201,116 @@ impl Session {
             pixel_height: 0,
         })?;
 
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".into());
-        let mut cmd = CommandBuilder::new(&shell);
+        let mut cmd = CommandBuilder::new(program);
+        cmd.args(args);
         cmd.cwd(&cwd);
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
 
         let child = pair.slave.spawn_command(cmd)?;
         drop(pair.slave);
 
-        let mut reader = pair.master.try_clone_reader()?;
-        let writer = pair.master.take_writer()?;
+        Self::from_raw(pair.master, child, cwd, rows, cols)
+    }
+
+    /// Build a session around a PTY whose child is already running, e.g. one
+    /// handed over by an external launcher.
+    ///
+    /// The session takes ownership of both halves: a reader thread is started on
+    /// a clone of `master` and lives until the PTY closes, and the master's
+    /// writer is taken, so nothing else may write to it afterwards. The slave
+    /// side should already be closed in this process so EOF is seen when the
+    /// child exits. `rows`/`cols` must match the PTY's current size, since the
+    /// parser starts at that size.
+    pub fn from_raw(
+        master: Box<dyn MasterPty + Send>,
+        child: Box<dyn portable_pty::Child + Send + Sync>,
+        cwd: PathBuf,
+        rows: u16,
+        cols: u16,
+    ) -> Result<Self> {
+        let pid = child.process_id();
+        let mut reader = master.try_clone_reader()?;
+        let writer = master.take_writer()?;
 
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = mpsc::sync_channel(PTY_CHANNEL_CAPACITY);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let dropped_by_reader = Arc::clone(&dropped);
 
         std::thread::spawn(move || {
             let mut buf = [0u8; 4096];
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => break,
-                    Ok(n) => {
-                        if tx.send(buf[..n].to_vec()).is_err() {
-                            break;
+                    Ok(n) => match tx.try_send(buf[..n].to_vec()) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            dropped_by_reader.fetch_add(n as u64, Ordering::Relaxed);
                         }
-                    }
+                        Err(TrySendError::Disconnected(_)) => break,
+                    },
                     Err(_) => break,
                 }
             }
         });
 
         let title_arc = Arc::new(Mutex::new(String::new()));
-        let tracker = TitleTracker {
+        let kitty_flags = Arc::new(Mutex::new(Vec::new()));
+        let tracker = Tracker {
             title: Arc::clone(&title_arc),
+            kitty_flags: Arc::clone(&kitty_flags),
         };
         let parser = vt100::Parser::new_with_callbacks(rows, cols, 0, tracker);
 
         Ok(Session {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name: dir_name(&cwd),
+            custom_name: false,
             pins: PinHistory::new(),
+            note: None,
             pin_pending_ts: None,
             project_path: cwd.to_string_lossy().to_string(),
+            pid,
+            paused: false,
+            started: false,
             window_title: title_arc,
+            kitty_flags,
             parser,
             pty_rx: rx,
-            master: pair.master,
+            dropped,
+            master,
             writer,
-            _child: child,
+            child,
+            status: SessionStatus::Running,
+            attention: false,
+            unseen_output: false,
+            last_output: None,
+            spawned_at: Instant::now(),
+            exited_at: None,
+            working: false,
+            ai_tools: default_ai_tools(),
+            command: Vec::new(),
+            env: HashMap::new(),
+            recording: None,
         })
     }
 
+    /// Drain pending PTY output. Returns nothing while paused, leaving the
+    /// backlog queued until output is resumed (or dropped once the queue fills).
     pub fn drain_raw_chunks(&mut self) -> Vec<Vec<u8>> {
         let mut chunks = Vec::new();
+        if self.paused {
+            return chunks;
+        }
         while let Ok(bytes) = self.pty_rx.try_recv() {
             chunks.push(bytes);
         }
+        if !chunks.is_empty() {
+            self.started = true;
+            self.last_output = Some(Instant::now());
+            self.working = true;
+        }
         chunks
     }
 
@@ -107,17 +318,213 @@ impl Session {
         self.parser.process(data);
     }
 
+    /// Feed output taken by `drain_raw_chunks` to the parser. Output of a session
+    /// that isn't `focused` is marked unseen.
+    pub fn ingest_output(&mut self, chunks: &[Vec<u8>], focused: bool) -> Result<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        for chunk in chunks {
+            self.feed_parser(chunk);
+        }
+        self.try_update_pin();
+        if !focused {
+            self.unseen_output = true;
+        }
+        self.record(|recording| recording.write_output(chunks))
+    }
+
+    /// Start recording raw output to an asciicast v2 file at `path`, replacing
+    /// any recording in progress. `timestamp` is the start time in Unix seconds.
+    pub fn start_recording(&mut self, path: &Path, timestamp: u64) -> Result<()> {
+        let (rows, cols) = self.parser.screen().size();
+        self.recording = Some(Recording::create(path, cols, rows, timestamp)?);
+        Ok(())
+    }
+
+    /// Stop recording, returning the file written.
+    pub fn stop_recording(&mut self) -> Option<PathBuf> {
+        self.recording.take().map(|r| r.path().to_path_buf())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Write to the recording, if any. A failed write ends it.
+    fn record(&mut self, write: impl FnOnce(&mut Recording) -> std::io::Result<()>) -> Result<()> {
+        let Some(recording) = self.recording.as_mut() else {
+            return Ok(());
+        };
+        if let Err(e) = write(recording) {
+            let path = recording.path().display().to_string();
+            self.recording = None;
+            return Err(anyhow::anyhow!("recording {path} stopped: {e}"));
+        }
+        Ok(())
+    }
+
+    /// Whether background output arrived since `mark_seen`.
+    pub fn has_unseen_output(&self) -> bool {
+        self.unseen_output
+    }
+
+    pub fn mark_seen(&mut self) {
+        self.unseen_output = false;
+    }
+
     pub fn screen(&self) -> &vt100::Screen {
         self.parser.screen()
     }
 
+    /// Keep up to `lines` rows of scrollback. Call right after spawning, before
+    /// any output has been fed to the parser, since the parser is replaced.
+    pub fn with_scrollback(mut self, lines: usize) -> Self {
+        let (rows, cols) = self.parser.screen().size();
+        let tracker = Tracker {
+            title: Arc::clone(&self.window_title),
+            kitty_flags: Arc::clone(&self.kitty_flags),
+        };
+        self.parser = vt100::Parser::new_with_callbacks(rows, cols, lines, tracker);
+        self
+    }
+
+    /// Recognize these AI tools instead of the defaults.
+    pub fn with_ai_tools(mut self, tools: &[AiTool]) -> Self {
+        self.set_ai_tools(tools);
+        self
+    }
+
+    pub fn set_ai_tools(&mut self, tools: &[AiTool]) {
+        self.ai_tools = tools.to_vec();
+    }
+
+    /// Rows the view is scrolled back from the live screen; 0 when following output.
+    pub fn scroll_offset(&self) -> usize {
+        self.parser.screen().scrollback()
+    }
+
+    /// Whether the view shows the program's live screen rather than scrollback.
+    pub fn is_live(&self) -> bool {
+        self.scroll_offset() == 0
+    }
+
+    /// Visit the scrollback and then the screen a page at a time, oldest first.
+    /// `f` gets the screen scrolled to each page and how many of its leading rows
+    /// were already visited. The view's scroll position is left as it was.
+    fn for_each_page(&mut self, mut f: impl FnMut(&vt100::Screen, usize)) {
+        let screen = self.parser.screen_mut();
+        let (rows, _) = screen.size();
+        let offset = screen.scrollback();
+        screen.set_scrollback(usize::MAX);
+        let len = screen.scrollback();
+        let mut seen = 0;
+        let mut page = len;
+        loop {
+            screen.set_scrollback(page);
+            let top = len - page;
+            f(screen, seen - top);
+            seen = top + rows as usize;
+            if page == 0 {
+                break;
+            }
+            page = page.saturating_sub(rows as usize);
+        }
+        screen.set_scrollback(offset);
+    }
+
+    /// Every row of scrollback followed by the screen, oldest first, as plain text.
+    pub fn all_lines(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        self.for_each_page(|screen, skip| {
+            let (_, cols) = screen.size();
+            lines.extend(screen.rows(0, cols).skip(skip));
+        });
+        lines
+    }
+
+    /// Write the scrollback and screen to `path`, as plain text or with the
+    /// program's colors and attributes kept as escape sequences.
+    pub fn export(&mut self, path: &Path, format: ExportFormat) -> Result<()> {
+        let mut out = Vec::new();
+        match format {
+            ExportFormat::PlainText => {
+                let mut lines = self.all_lines();
+                while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                    lines.pop();
+                }
+                for line in lines {
+                    out.extend_from_slice(line.trim_end().as_bytes());
+                    out.push(b'\n');
+                }
+            }
+            ExportFormat::Ansi => self.for_each_page(|screen, skip| {
+                let (_, cols) = screen.size();
+                for row in screen.rows_formatted(0, cols).skip(skip) {
+                    out.extend_from_slice(&row);
+                    out.extend_from_slice(b"\x1b[0m\n");
+                }
+            }),
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// Scroll so `line`, numbered as in `all_lines`, is on screen, near the
+    /// middle where possible. Returns the screen row it landed on.
+    pub fn scroll_to_line(&mut self, line: usize) -> u16 {
+        let screen = self.parser.screen_mut();
+        let (rows, _) = screen.size();
+        screen.set_scrollback(usize::MAX);
+        let len = screen.scrollback();
+        let top = line.saturating_sub(rows as usize / 2).min(len);
+        screen.set_scrollback(len - top);
+        (line - top) as u16
+    }
+
+    /// Scroll the view by `lines`, positive toward older output, within the
+    /// scrollback held.
+    pub fn scroll_by(&mut self, lines: isize) {
+        let offset = self.scroll_offset().saturating_add_signed(lines);
+        self.parser.screen_mut().set_scrollback(offset);
+    }
+
     pub fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
         self.writer.write_all(data)?;
         self.writer.flush()?;
         Ok(())
     }
 
-    pub fn resize(&mut self, rows: u16, cols: u16) -> Result<()> {
+    /// Type `cmd` and press Enter, as if entered at the keyboard. In an AI tool the
+    /// command is pinned like a typed prompt.
+    pub fn run_command(&mut self, cmd: &str) -> Result<()> {
+        self.write_bytes(format!("{cmd}\r").as_bytes())?;
+        if self.is_ai_tool() {
+            self.pins.push(cmd.to_string());
+            self.pin_pending_ts = Some(now_ms());
+        }
+        Ok(())
+    }
+
+    /// Resize the PTY and the parser together. Sizes are clamped to at least
+    /// 1x1, and the parser is only resized once the PTY accepted the new size,
+    /// so a failed resize leaves both at the old size.
+    ///
+    /// Output still queued was written for the old size, so it is parsed first
+    /// and returned for the caller to forward if it shows this session. It is
+    /// returned even when recording it or the resize itself failed.
+    pub fn resize(&mut self, rows: u16, cols: u16) -> (Vec<Vec<u8>>, Result<()>) {
+        let chunks = self.drain_raw_chunks();
+        let ingested = self.ingest_output(&chunks, true);
+        let resized = self.resize_pty(rows, cols);
+        (chunks, ingested.and(resized))
+    }
+
+    fn resize_pty(&mut self, rows: u16, cols: u16) -> Result<()> {
+        let (rows, cols) = (rows.max(1), cols.max(1));
         self.master.resize(PtySize {
             rows,
             cols,
@@ -125,7 +532,144 @@ impl Session {
             pixel_height: 0,
         })?;
         self.parser.screen_mut().set_size(rows, cols);
-        Ok(())
+        self.record(|recording| recording.write_resize(cols, rows))
+    }
+
+    /// True until the child produces its first output.
+    pub fn is_starting(&self) -> bool {
+        !self.started
+    }
+
+    /// Check whether the child has exited without blocking. Returns its exit
+    /// code once, on the call that first observes the exit.
+    pub fn poll_exit(&mut self) -> Option<u32> {
+        if self.status != SessionStatus::Running {
+            return None;
+        }
+        let code = self.child.try_wait().ok()??.exit_code();
+        self.status = SessionStatus::Exited(code);
+        self.exited_at = Some(Instant::now());
+        Some(code)
+    }
+
+    /// Close the session. A running program gets SIGHUP, as when a terminal
+    /// window closes, so shells can run their exit traps; it is killed if still
+    /// running after `grace`. SIGTERM isn't sent as well, since it would end a
+    /// script before its HUP trap runs. The wait happens on a thread, so this returns at once.
+    pub fn terminate(self, grace: Duration) {
+        let mut child = self.child;
+        if self.status != SessionStatus::Running {
+            return;
+        }
+        let Some(pid) = self.pid else {
+            child.kill().ok();
+            return;
+        };
+        // The child leads its own session and process group, so jobs it started
+        // are reached too
+        signal_group(pid, Signal::Hangup);
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + grace;
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            // Still unreaped, so the pid (and its process group) can't have been reused
+            signal_group(pid, Signal::Kill);
+            child.kill().ok();
+            child.wait().ok();
+        });
+    }
+
+    /// Send `signal` to the terminal's foreground process group, the way the
+    /// terminal driver does for Ctrl+C. This reaches programs that turned off
+    /// ISIG and read Ctrl+C as a plain byte. Returns false when nothing was
+    /// signalled.
+    pub fn send_signal(&self, signal: Signal) -> bool {
+        if self.status != SessionStatus::Running {
+            return false;
+        }
+        let pgid = self
+            .master
+            .process_group_leader()
+            .and_then(|pgid| u32::try_from(pgid).ok())
+            .or(self.pid);
+        pgid.is_some_and(|pgid| signal_group(pgid, signal))
+    }
+
+    /// How long the program has been running, or ran for once it exited.
+    pub fn uptime(&self) -> Duration {
+        let end = self.exited_at.unwrap_or_else(Instant::now);
+        end.duration_since(self.spawned_at)
+    }
+
+    /// Time since the program last wrote anything, while it is running.
+    pub fn quiet_for(&self) -> Option<Duration> {
+        (self.status == SessionStatus::Running)
+            .then(|| self.last_output.unwrap_or(self.spawned_at).elapsed())
+    }
+
+    /// Uptime for the hint bar, and the quiet time once output has stopped for a
+    /// minute.
+    pub fn runtime_labels(&self) -> (String, Option<String>) {
+        let quiet = self
+            .quiet_for()
+            .filter(|quiet| *quiet >= Duration::from_secs(60))
+            .map(compact_duration);
+        (compact_duration(self.uptime()), quiet)
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        self.status
+    }
+
+    /// Output bytes lost because the UI fell behind the program.
+    pub fn dropped_bytes(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop or resume forwarding output for a runaway session.
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// PID of the spawned child, if the platform reports one.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Program and arguments the session was spawned with; empty for `$SHELL`.
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// Extra environment the session was spawned with.
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    pub fn project_path(&self) -> &str {
+        &self.project_path
+    }
+
+    /// The child's current directory where the platform reports it, otherwise
+    /// the directory the session was spawned in.
+    pub fn current_dir(&self) -> PathBuf {
+        self.pid
+            .and_then(proc_name::cwd)
+            .unwrap_or_else(|| PathBuf::from(&self.project_path))
+    }
+
+    /// Whether the directory the session was spawned in still exists. It can be
+    /// removed while a long-lived session runs.
+    pub fn cwd_exists(&self) -> bool {
+        Path::new(&self.project_path).is_dir()
     }
 
     pub fn window_title(&self) -> String {
@@ -135,16 +679,80 @@ impl Session {
             .unwrap_or_default()
     }
 
+    /// Whether the program turned on the kitty keyboard protocol, and so reads
+    /// keys without a control byte (Ctrl+1) as `CSI <codepoint>;<mod>u`.
+    pub fn kitty_keyboard(&self) -> bool {
+        self.kitty_flags
+            .lock()
+            .is_ok_and(|stack| stack.last().is_some_and(|&flags| flags != 0))
+    }
+
     pub fn is_ai_tool(&self) -> bool {
-        let matched_proc = self
+        self.ai_tool().is_some()
+    }
+
+    /// The AI tool running in the session, matched by the foreground process's
+    /// name and then by the window title.
+    pub fn ai_tool(&self) -> Option<&AiTool> {
+        let by_name = self
             .master
             .process_group_leader()
             .and_then(proc_name::from_pid)
-            .is_some_and(|name| is_ai_tool_name(&name));
-        if matched_proc {
-            return true;
+            .and_then(|name| ai_tool_by_name(&self.ai_tools, &name));
+        by_name.or_else(|| ai_tool_by_title(&self.ai_tools, &self.window_title()))
+    }
+
+    /// Heuristic idle check for plain shells: the cursor sits right after one of
+    /// `prompt_suffixes` on the last non-empty row.
+    pub fn is_at_prompt(&self, prompt_suffixes: &[String]) -> bool {
+        shell::is_at_prompt(self.parser.screen(), prompt_suffixes)
+    }
+
+    /// AI tools only: busy or idle going by `busy`/`idle` markers on screen.
+    pub fn ai_activity(&self, busy: &[String], idle: &[String]) -> AiActivity {
+        activity::ai_activity(self.parser.screen(), busy, idle)
+    }
+
+    /// AI tools only: a selection menu (a permission prompt or a question) is
+    /// waiting for an answer rather than the usual input box.
+    pub fn needs_attention(&self) -> bool {
+        let screen = self.parser.screen();
+        prompt::extract_input_area(screen).is_none()
+            && selection::extract_selected_option(screen).is_some()
+    }
+
+    /// Re-check `needs_attention` for an AI tool. Returns true only on the
+    /// transition into needing attention.
+    pub fn update_attention(&mut self) -> bool {
+        let now = self.is_ai_tool() && self.needs_attention();
+        let rose = now && !self.attention;
+        self.attention = now;
+        rose
+    }
+
+    /// True once each time output that had been arriving stays quiet for `quiet`
+    /// and an AI tool's input box is back on screen: the tool finished its turn.
+    pub fn update_finished(&mut self, quiet: Duration) -> bool {
+        if !self.working || self.last_output.is_some_and(|t| t.elapsed() < quiet) {
+            return false;
         }
-        is_ai_tool_title(&self.window_title())
+        self.working = false;
+        self.is_ai_tool() && prompt::extract_input_area(self.parser.screen()).is_some()
+    }
+
+    /// The prompt currently typed into an AI tool's input box, if any.
+    pub fn draft_prompt(&self) -> Option<String> {
+        prompt::extract_input_area(self.parser.screen()).filter(|text| !text.is_empty())
+    }
+
+    /// Pin what is being typed without submitting it. Returns false when the
+    /// input area is empty or not found.
+    pub fn pin_draft(&mut self) -> bool {
+        let Some(text) = self.draft_prompt() else {
+            return false;
+        };
+        self.pins.push(text);
+        true
     }
 
     /// Extract prompt from the screen and save as PIN.
@@ -186,6 +794,50 @@ impl Session {
     }
 }
 
+/// Session name derived from the last component of its working directory.
+fn dir_name(cwd: &Path) -> String {
+    cwd.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| cwd.to_string_lossy().to_string())
+}
+
+/// Names for a set of session directories: the basename, or as many trailing
+/// components as needed to tell apart directories that share a basename
+/// (`work/api/src` and `play/api/src` become `work/api/src` and `play/api/src`,
+/// `a/lib` and `b/lib` become `a/lib` and `b/lib`).
+pub fn display_names(paths: &[&Path]) -> Vec<String> {
+    let components: Vec<Vec<String>> = paths
+        .iter()
+        .map(|p| {
+            p.components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect()
+        })
+        .collect();
+    let suffix = |parts: &[String], k: usize| parts[parts.len().saturating_sub(k)..].to_vec();
+
+    components
+        .iter()
+        .enumerate()
+        .map(|(i, parts)| {
+            let mut k = 1;
+            while k < parts.len()
+                && components.iter().enumerate().any(|(j, other)| {
+                    j != i && other != parts && suffix(other, k) == suffix(parts, k)
+                })
+            {
+                k += 1;
+            }
+            let tail = suffix(parts, k);
+            if tail.is_empty() {
+                dir_name(paths[i])
+            } else {
+                PathBuf::from_iter(tail).to_string_lossy().to_string()
+            }
+        })
+        .collect()
+}
+
 fn now_ms() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -193,29 +845,578 @@ fn now_ms() -> u128 {
         .unwrap_or(0)
 }
 
-const AI_TOOL_KEYWORDS: &[&str] = &["claude", "codex"];
+/// An AI coding tool murmur recognizes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AiTool {
+    /// Matches a process with exactly this name, or a window title containing
+    /// it, ignoring ASCII case.
+    pub pattern: String,
+    /// Shown in the hint bar while the tool runs.
+    pub name: String,
+    #[serde(default)]
+    pub glyph: String,
+}
 
-fn is_ai_tool_name(name: &str) -> bool {
-    AI_TOOL_KEYWORDS
+pub fn default_ai_tools() -> Vec<AiTool> {
+    [("claude", "Claude Code"), ("codex", "Codex")]
+        .into_iter()
+        .map(|(pattern, name)| AiTool {
+            pattern: pattern.to_string(),
+            name: name.to_string(),
+            glyph: "\u{2726}".to_string(),
+        })
+        .collect()
+}
+
+fn ai_tool_by_name<'a>(tools: &'a [AiTool], name: &str) -> Option<&'a AiTool> {
+    tools
         .iter()
-        .any(|kw| name.eq_ignore_ascii_case(kw))
+        .find(|tool| name.eq_ignore_ascii_case(&tool.pattern))
 }
 
-fn is_ai_tool_title(title: &str) -> bool {
-    AI_TOOL_KEYWORDS.iter().any(|kw| {
-        title
-            .as_bytes()
-            .windows(kw.len())
-            .any(|w| w.eq_ignore_ascii_case(kw.as_bytes()))
+fn ai_tool_by_title<'a>(tools: &'a [AiTool], title: &str) -> Option<&'a AiTool> {
+    tools.iter().find(|tool| {
+        let kw = tool.pattern.as_bytes();
+        !kw.is_empty()
+            && title
+                .as_bytes()
+                .windows(kw.len())
+                .any(|w| w.eq_ignore_ascii_case(kw))
     })
 }
 
+/// Where an export of session `name` taken at `timestamp` (Unix seconds) goes:
+/// `<home>/.murmur/<name>-<timestamp>.log`.
+pub fn export_path(home: &Path, name: &str, timestamp: u64) -> PathBuf {
+    murmur_file(home, name, timestamp, "log")
+}
+
+/// Where a recording of session `name` started at `timestamp` goes:
+/// `<home>/.murmur/<name>-<timestamp>.cast`.
+pub fn recording_path(home: &Path, name: &str, timestamp: u64) -> PathBuf {
+    murmur_file(home, name, timestamp, "cast")
+}
+
+fn murmur_file(home: &Path, name: &str, timestamp: u64, extension: &str) -> PathBuf {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c == '/' || c.is_whitespace() {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect();
+    home.join(".murmur")
+        .join(format!("{name}-{timestamp}.{extension}"))
+}
+
+/// Signals murmur sends to a session's processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Interrupt,
+    Hangup,
+    Kill,
+}
+
+/// Signal every process in the group led by `pgid`. Returns false when the
+/// group is gone.
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: Signal) -> bool {
+    let signal = match signal {
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Kill => libc::SIGKILL,
+    };
+    // SAFETY: kill(2) takes no pointers
+    unsafe { libc::kill(-(pgid as libc::pid_t), signal) == 0 }
+}
+
+#[cfg(not(unix))]
+fn signal_group(_pgid: u32, _signal: Signal) -> bool {
+    false
+}
+
+/// `d` for the hint bar in its two largest units, to the minute: `<1m`, `12m`,
+/// `1h5m`, `2d3h`.
+pub fn compact_duration(d: Duration) -> String {
+    let mins = d.as_secs() / 60;
+    let (hours, days) = (mins / 60, mins / (60 * 24));
+    match mins {
+        0 => "<1m".to_string(),
+        1..60 => format!("{mins}m"),
+        _ if days > 0 => match hours % 24 {
+            0 => format!("{days}d"),
+            h => format!("{days}d{h}h"),
+        },
+        _ => match mins % 60 {
+            0 => format!("{hours}h"),
+            m => format!("{hours}h{m}m"),
+        },
+    }
+}
+
+/// `path` for display, with `home` shortened to `~`.
+pub fn tilde_path(path: &Path, home: &str) -> String {
+    match path.strip_prefix(home) {
+        Ok(rest) if !home.is_empty() && rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) if !home.is_empty() => format!("~/{}", rest.display()),
+        _ => path.display().to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ── status ───────────────────────────────────────────────────────
+
+    #[test]
+    fn poll_exit_reports_exit_code_once() {
+        let args = ["-c".to_string(), "exit 3".to_string()];
+        let mut session =
+            Session::spawn_with_command(std::env::temp_dir(), 24, 80, "sh", &args).unwrap();
+        assert_eq!(session.status(), SessionStatus::Running);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let code = loop {
+            if let Some(code) = session.poll_exit() {
+                break code;
+            }
+            assert!(std::time::Instant::now() < deadline, "child never exited");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(code, 3);
+        assert_eq!(session.status(), SessionStatus::Exited(3));
+        assert_eq!(session.poll_exit(), None);
+    }
+
+    fn sh_session(script: &str) -> Session {
+        let args = ["-c".to_string(), script.to_string()];
+        Session::spawn_with_command(std::env::temp_dir(), 24, 80, "sh", &args).unwrap()
+    }
+
+    #[cfg(unix)]
+    fn wait_gone(pid: u32) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        // SAFETY: signal 0 only checks that the process exists
+        while unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+            assert!(std::time::Instant::now() < deadline, "child still running");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_lets_the_program_run_its_trap() {
+        let marker = std::env::temp_dir().join(format!("murmur-hup-{}", std::process::id()));
+        std::fs::remove_file(&marker).ok();
+        let script = format!(
+            "trap 'echo bye > {}; exit' HUP; while :; do sleep 0.05; done",
+            marker.display()
+        );
+        let session = sh_session(&script);
+        // Give sh time to install the trap
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let pid = session.pid().unwrap();
+        session.terminate(std::time::Duration::from_secs(5));
+        wait_gone(pid);
+        assert_eq!(std::fs::read_to_string(&marker).unwrap(), "bye\n");
+        std::fs::remove_file(&marker).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn send_signal_interrupts_the_foreground_group() {
+        let mut session = sh_session("stty -isig; trap 'exit 7' INT; while :; do sleep 0.05; done");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        // With ISIG off the byte alone does nothing
+        session.write_bytes(b"\x03").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(session.poll_exit(), None);
+        assert!(session.send_signal(Signal::Interrupt));
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let code = loop {
+            if let Some(code) = session.poll_exit() {
+                break code;
+            }
+            assert!(std::time::Instant::now() < deadline, "child never exited");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        assert_eq!(code, 7);
+        assert!(!session.send_signal(Signal::Interrupt));
+    }
+
+    #[test]
+    fn run_command_submits_the_line() {
+        let mut session = sh_session("read line; echo \"got:$line\"; sleep 5");
+        session.run_command("hi there").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !session.screen().contents().contains("got:hi there") {
+            assert!(std::time::Instant::now() < deadline, "command never ran");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            let chunks = session.drain_raw_chunks();
+            session.ingest_output(&chunks, true).unwrap();
+        }
+        // A shell is not an AI tool, so nothing is pinned
+        assert_eq!(session.pins.current(), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_kills_after_the_grace_period() {
+        let session = sh_session("trap '' HUP TERM; while :; do sleep 0.05; done");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let pid = session.pid().unwrap();
+        let start = std::time::Instant::now();
+        session.terminate(std::time::Duration::from_millis(100));
+        assert!(start.elapsed() < std::time::Duration::from_millis(100));
+        wait_gone(pid);
+    }
+
+    #[test]
+    fn background_output_is_unseen_until_marked() {
+        let mut session = cat_session(0);
+        assert!(!session.has_unseen_output());
+        session.write_bytes(b"hi\n").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let chunks = session.drain_raw_chunks();
+            if !chunks.is_empty() {
+                session.ingest_output(&chunks, false).unwrap();
+                break;
+            }
+            assert!(std::time::Instant::now() < deadline, "no output");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(session.has_unseen_output());
+        session.mark_seen();
+        assert!(!session.has_unseen_output());
+    }
+
+    #[test]
+    fn ingest_output_renders_the_same_focused_or_not() {
+        let chunks: Vec<Vec<u8>> = vec![
+            b"one\r\ntw".to_vec(),
+            b"o\r\n\x1b[1mbold\x1b[0m ".to_vec(),
+            "\u{4F60}".as_bytes()[..2].to_vec(),
+            "\u{4F60}".as_bytes()[2..].to_vec(),
+        ];
+        let mut focused = cat_session(0);
+        let mut background = cat_session(0);
+        focused.ingest_output(&chunks, true).unwrap();
+        background.ingest_output(&chunks, false).unwrap();
+        let contents = focused.screen().contents();
+        assert_eq!(contents, "one\ntwo\nbold \u{4F60}");
+        assert_eq!(background.screen().contents(), contents);
+        assert!(!focused.has_unseen_output());
+        assert!(background.has_unseen_output());
+    }
+
+    #[test]
+    fn kitty_keyboard_follows_pushed_flags() {
+        let mut session = cat_session(0);
+        let feed = |bytes: &[u8], session: &mut Session| {
+            session.ingest_output(&[bytes.to_vec()], true).unwrap();
+            session.kitty_keyboard()
+        };
+        assert!(!session.kitty_keyboard());
+        assert!(feed(b"\x1b[>1u", &mut session));
+        assert!(!feed(b"\x1b[>0u", &mut session));
+        assert!(feed(b"\x1b[<u", &mut session));
+        assert!(!feed(b"\x1b[=1;3u", &mut session));
+        assert!(!feed(b"\x1b[<5u", &mut session));
+    }
+
+    #[test]
+    fn update_finished_waits_for_quiet_and_skips_shells() {
+        let mut session = cat_session(0);
+        session.write_bytes(b"hi\n").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while session.drain_raw_chunks().is_empty() {
+            assert!(std::time::Instant::now() < deadline, "no output");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!session.update_finished(Duration::from_secs(60)));
+        assert!(session.working);
+        // Quiet long enough, but cat is no AI tool
+        assert!(!session.update_finished(Duration::ZERO));
+        assert!(!session.working);
+    }
+
+    // ── scrollback ───────────────────────────────────────────────────
+
+    fn cat_session(scrollback: usize) -> Session {
+        Session::spawn_with_command(std::env::temp_dir(), 5, 20, "cat", &[])
+            .unwrap()
+            .with_scrollback(scrollback)
+    }
+
+    #[test]
+    fn scroll_by_clamps_to_scrollback() {
+        let mut session = cat_session(100);
+        for i in 0..20 {
+            session.feed_parser(format!("line {i}\r\n").as_bytes());
+        }
+        session.scroll_by(3);
+        assert_eq!(session.scroll_offset(), 3);
+        assert!(session.screen().contents().starts_with("line 13"));
+        session.scroll_by(-10);
+        assert_eq!(session.scroll_offset(), 0);
+        session.scroll_by(1000);
+        assert_eq!(session.scroll_offset(), 16);
+    }
+
+    #[test]
+    fn scroll_view_stays_put_while_output_arrives() {
+        let mut session = cat_session(100);
+        for i in 0..20 {
+            session.feed_parser(format!("line {i}\r\n").as_bytes());
+        }
+        session.scroll_by(3);
+        let before = session.screen().contents();
+        session.feed_parser(b"more\r\n");
+        assert_eq!(session.screen().contents(), before);
+    }
+
+    #[test]
+    fn all_lines_covers_scrollback_and_screen() {
+        let mut session = cat_session(100);
+        for i in 0..12 {
+            session.feed_parser(format!("line {i}\r\n").as_bytes());
+        }
+        session.scroll_by(2);
+        let lines = session.all_lines();
+        assert_eq!(lines.len(), 8 + 5);
+        assert_eq!(lines[0], "line 0");
+        assert_eq!(lines[11], "line 11");
+        assert_eq!(session.scroll_offset(), 2);
+    }
+
+    #[test]
+    fn scroll_to_line_brings_line_on_screen() {
+        let mut session = cat_session(100);
+        for i in 0..30 {
+            session.feed_parser(format!("line {i}\r\n").as_bytes());
+        }
+        let row = session.scroll_to_line(3);
+        let contents = session.screen().contents();
+        assert_eq!(contents.lines().nth(row as usize), Some("line 3"));
+        let row = session.scroll_to_line(29);
+        assert_eq!(session.scroll_offset(), 0);
+        assert_eq!(
+            session.screen().contents().lines().nth(row as usize),
+            Some("line 29")
+        );
+    }
+
+    #[test]
+    fn export_plain_and_ansi() {
+        let mut session = cat_session(100);
+        for i in 0..8 {
+            session.feed_parser(format!("line {i}\r\n").as_bytes());
+        }
+        session.feed_parser(b"\x1b[31mred\x1b[0m");
+        let dir = std::env::temp_dir().join(format!("murmur-export-{}", std::process::id()));
+
+        let plain = dir.join("plain.log");
+        session.export(&plain, ExportFormat::PlainText).unwrap();
+        let text = std::fs::read_to_string(&plain).unwrap();
+        assert!(text.starts_with("line 0\nline 1\n"));
+        assert!(text.ends_with("line 7\nred\n"));
+
+        let ansi = dir.join("ansi.log");
+        session.export(&ansi, ExportFormat::Ansi).unwrap();
+        let text = std::fs::read_to_string(&ansi).unwrap();
+        assert!(text.starts_with("line 0"));
+        assert!(text.contains("\x1b[31mred"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn recording_captures_raw_output() {
+        let dir = std::env::temp_dir().join(format!("murmur-record-{}", std::process::id()));
+        let path = dir.join("cat.cast");
+        let mut session = cat_session(0);
+        session.start_recording(&path, 0).unwrap();
+        assert!(session.is_recording());
+        session
+            .ingest_output(&[b"\x1b[1mbold\x1b[0m".to_vec()], true)
+            .unwrap();
+        session.resize(6, 30).1.unwrap();
+        assert_eq!(session.stop_recording(), Some(path.clone()));
+        assert!(!session.is_recording());
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].contains(r#""width": 20, "height": 5"#));
+        assert!(lines[1].ends_with(r#""o", "\u001b[1mbold\u001b[0m"]"#));
+        assert!(lines[2].ends_with(r#""r", "30x6"]"#));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn export_path_flattens_name() {
+        assert_eq!(
+            export_path(Path::new("/home/me"), "a/src", 1700000000),
+            Path::new("/home/me/.murmur/a-src-1700000000.log")
+        );
+    }
+
+    #[test]
+    fn compact_duration_keeps_two_units() {
+        let mins = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(compact_duration(Duration::from_secs(59)), "<1m");
+        assert_eq!(compact_duration(mins(12)), "12m");
+        assert_eq!(compact_duration(mins(60)), "1h");
+        assert_eq!(compact_duration(mins(65)), "1h5m");
+        assert_eq!(compact_duration(mins(24 * 60)), "1d");
+        assert_eq!(compact_duration(mins(51 * 60 + 7)), "2d3h");
+    }
+
+    #[test]
+    fn uptime_stops_at_exit() {
+        let mut session = sh_session("exit 0");
+        assert!(session.quiet_for().is_some());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while session.poll_exit().is_none() {
+            assert!(std::time::Instant::now() < deadline, "child never exited");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let ran = session.uptime();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert_eq!(session.uptime(), ran);
+        assert_eq!(session.quiet_for(), None);
+    }
+
+    #[test]
+    fn tilde_path_shortens_home() {
+        assert_eq!(tilde_path(Path::new("/home/me"), "/home/me"), "~");
+        assert_eq!(
+            tilde_path(Path::new("/home/me/api/src"), "/home/me"),
+            "~/api/src"
+        );
+        assert_eq!(tilde_path(Path::new("/home/meg"), "/home/me"), "/home/meg");
+        assert_eq!(tilde_path(Path::new("/tmp"), ""), "/tmp");
+    }
+
+    #[test]
+    fn pin_draft_pins_typed_text() {
+        let mut session = cat_session(0);
+        assert!(!session.pin_draft());
+        session.feed_parser("\u{2500}\u{2500}\u{2500}\u{2500}\r\n".as_bytes());
+        session.feed_parser("\u{276f}\u{a0}half typed\r\n".as_bytes());
+        session.feed_parser("\u{2500}\u{2500}\u{2500}\u{2500}".as_bytes());
+        assert!(session.pin_draft());
+        assert_eq!(session.pins.current(), "half typed");
+    }
+
+    #[test]
+    fn no_scrollback_cannot_scroll() {
+        let mut session = cat_session(0);
+        session.feed_parser(b"a\r\nb\r\nc\r\nd\r\ne\r\nf\r\n");
+        session.scroll_by(2);
+        assert_eq!(session.scroll_offset(), 0);
+    }
+
+    // ── resize ───────────────────────────────────────────────────────
+
+    #[test]
+    fn resize_clamps_zero_size() {
+        let mut session =
+            Session::spawn_with_command(std::env::temp_dir(), 24, 80, "cat", &[]).unwrap();
+        session.resize(0, 0).1.unwrap();
+        assert_eq!(session.screen().size(), (1, 1));
+        session.resize(10, 40).1.unwrap();
+        assert_eq!(session.screen().size(), (10, 40));
+    }
+
+    #[test]
+    fn resize_parses_queued_output_at_the_old_size() {
+        let mut session =
+            Session::spawn_with_command(std::env::temp_dir(), 24, 80, "cat", &[]).unwrap();
+        // Queued by hand, so nothing depends on when the reader thread gets to it
+        let (tx, rx) = mpsc::sync_channel(1);
+        session.pty_rx = rx;
+        tx.send("0".repeat(40).into_bytes()).unwrap();
+        let (chunks, result) = session.resize(24, 20);
+        result.unwrap();
+        assert_eq!(chunks.concat(), "0".repeat(40).into_bytes());
+        // Written on one 80-column row, then cut to 20 columns rather than wrapped
+        let rows: Vec<String> = session.screen().rows(0, 20).take(2).collect();
+        assert_eq!(rows, ["0".repeat(20), String::new()]);
+    }
+
+    // ── dir_name ─────────────────────────────────────────────────────
+
+    #[test]
+    fn dir_name_basename() {
+        assert_eq!(dir_name(Path::new("/home/user/proj")), "proj");
+    }
+
+    #[test]
+    fn dir_name_root() {
+        assert_eq!(dir_name(Path::new("/")), "/");
+    }
+
+    // ── display_names ────────────────────────────────────────────────
+
+    fn names(paths: &[&str]) -> Vec<String> {
+        let paths: Vec<&Path> = paths.iter().map(Path::new).collect();
+        display_names(&paths)
+    }
+
+    #[test]
+    fn display_names_unique_basenames() {
+        assert_eq!(names(&["/a/api", "/b/web"]), vec!["api", "web"]);
+    }
+
+    #[test]
+    fn display_names_colliding_basenames() {
+        assert_eq!(
+            names(&["/home/u/work/api/src", "/home/u/play/api/src", "/x/lib"]),
+            vec!["work/api/src", "play/api/src", "lib"]
+        );
+        assert_eq!(names(&["/a/lib", "/b/lib"]), vec!["a/lib", "b/lib"]);
+    }
+
+    #[test]
+    fn display_names_same_directory_twice() {
+        assert_eq!(names(&["/a/lib", "/a/lib"]), vec!["lib", "lib"]);
+    }
+
+    #[test]
+    fn display_names_root() {
+        assert_eq!(names(&["/"]), vec!["/"]);
+    }
+
     // ── is_ai_tool_title ─────────────────────────────────────────────
 
+    fn is_ai_tool_title(title: &str) -> bool {
+        ai_tool_by_title(&default_ai_tools(), title).is_some()
+    }
+
+    fn is_ai_tool_name(name: &str) -> bool {
+        ai_tool_by_name(&default_ai_tools(), name).is_some()
+    }
+
+    #[test]
+    fn ai_tool_configured_patterns() {
+        let tools = vec![AiTool {
+            pattern: "aider".to_string(),
+            name: "Aider".to_string(),
+            glyph: String::new(),
+        }];
+        assert_eq!(ai_tool_by_name(&tools, "aider").unwrap().name, "Aider");
+        assert!(ai_tool_by_title(&tools, "aider v0.80").is_some());
+        assert!(ai_tool_by_title(&tools, "Claude Code").is_none());
+        assert_eq!(
+            ai_tool_by_title(&default_ai_tools(), "codex").unwrap().name,
+            "Codex"
+        );
+    }
+
     #[test]
     fn ai_tool_claude() {
         assert!(is_ai_tool_title("Claude Code"));
@@ -24,3 +24,14 @@ pub fn from_pid(pid: libc::pid_t) -> Option<String> {
 pub fn from_pid(_pid: i32) -> Option<String> {
     None
 }
+
+/// Current working directory of a process, where the platform exposes it.
+#[cfg(target_os = "linux")]
+pub fn cwd(pid: u32) -> Option<std::path::PathBuf> {
+    std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cwd(_pid: u32) -> Option<std::path::PathBuf> {
+    None
+}
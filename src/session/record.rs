@@ -0,0 +1,164 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// A session's raw output being written to an asciicast v2 file: a JSON header
+/// line, then one `[seconds, "o", text]` line per chunk.
+pub struct Recording {
+    path: PathBuf,
+    file: BufWriter<File>,
+    started: Instant,
+    /// Start of a UTF-8 character cut off at the end of the last chunk.
+    partial: Vec<u8>,
+}
+
+impl Recording {
+    /// Create `path` and write the header for a `cols`x`rows` terminal.
+    /// `timestamp` is the start time in Unix seconds.
+    pub fn create(path: &Path, cols: u16, rows: u16, timestamp: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = BufWriter::new(File::create(path)?);
+        let env = ["SHELL", "TERM"]
+            .into_iter()
+            .filter_map(|key| Some((key, std::env::var(key).ok()?)))
+            .collect::<Vec<_>>();
+        writeln!(file, "{}", header(cols, rows, timestamp, &env))?;
+        file.flush()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            started: Instant::now(),
+            partial: Vec::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an output event for the bytes of `chunks`, as they came from the PTY.
+    pub fn write_output(&mut self, chunks: &[Vec<u8>]) -> io::Result<()> {
+        let secs = self.started.elapsed().as_secs_f64();
+        for chunk in chunks {
+            self.partial.extend_from_slice(chunk);
+            let text = take_utf8(&mut self.partial);
+            if !text.is_empty() {
+                writeln!(self.file, "{}", event(secs, "o", &text))?;
+            }
+        }
+        self.file.flush()
+    }
+
+    /// Append a resize event.
+    pub fn write_resize(&mut self, cols: u16, rows: u16) -> io::Result<()> {
+        let secs = self.started.elapsed().as_secs_f64();
+        writeln!(self.file, "{}", event(secs, "r", &format!("{cols}x{rows}")))?;
+        self.file.flush()
+    }
+}
+
+fn header(cols: u16, rows: u16, timestamp: u64, env: &[(&str, String)]) -> String {
+    let env: Vec<String> = env
+        .iter()
+        .map(|(key, value)| format!("{}: {}", json_string(key), json_string(value)))
+        .collect();
+    format!(
+        "{{\"version\": 2, \"width\": {cols}, \"height\": {rows}, \"timestamp\": {timestamp}, \"env\": {{{}}}}}",
+        env.join(", ")
+    )
+}
+
+fn event(secs: f64, kind: &str, data: &str) -> String {
+    format!("[{secs:.6}, {}, {}]", json_string(kind), json_string(data))
+}
+
+/// Take the text from the front of `buf`, leaving an incomplete trailing
+/// character for the next chunk. Invalid bytes become U+FFFD.
+fn take_utf8(buf: &mut Vec<u8>) -> String {
+    let keep = match std::str::from_utf8(buf) {
+        Ok(_) => 0,
+        // `error_len` is None only when the input ends mid-character
+        Err(e) if e.error_len().is_none() => buf.len() - e.valid_up_to(),
+        Err(_) => 0,
+    };
+    let rest = buf.split_off(buf.len() - keep);
+    let text = String::from_utf8_lossy(buf).into_owned();
+    *buf = rest;
+    text
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes_controls() {
+        assert_eq!(json_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(json_string("\x1b[1m\r\n"), r#""\u001b[1m\r\n""#);
+    }
+
+    #[test]
+    fn header_and_events_are_asciicast_v2() {
+        let env = [("TERM", "xterm-256color".to_string())];
+        assert_eq!(
+            header(80, 24, 1700000000, &env),
+            r#"{"version": 2, "width": 80, "height": 24, "timestamp": 1700000000, "env": {"TERM": "xterm-256color"}}"#
+        );
+        assert_eq!(event(1.5, "o", "hi\n"), r#"[1.500000, "o", "hi\n"]"#);
+    }
+
+    #[test]
+    fn take_utf8_holds_back_a_split_character() {
+        let bytes = "h\u{e9}".as_bytes();
+        let mut buf = bytes[..2].to_vec();
+        assert_eq!(take_utf8(&mut buf), "h");
+        buf.extend_from_slice(&bytes[2..]);
+        assert_eq!(take_utf8(&mut buf), "\u{e9}");
+        assert!(buf.is_empty());
+
+        let mut invalid = b"a\xffb".to_vec();
+        assert_eq!(take_utf8(&mut invalid), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn recording_writes_header_then_output() {
+        let path = std::env::temp_dir()
+            .join(format!("murmur-rec-{}", std::process::id()))
+            .join("session.cast");
+        let mut recording = Recording::create(&path, 80, 24, 0).unwrap();
+        recording
+            .write_output(&[b"\x1b[31mred".to_vec(), b"\r\n".to_vec()])
+            .unwrap();
+        recording.write_resize(100, 30).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with(r#"{"version": 2, "width": 80, "height": 24"#));
+        assert!(lines[1].ends_with(r#", "o", "\u001b[31mred"]"#));
+        assert!(lines[2].ends_with(r#", "o", "\r\n"]"#));
+        assert!(lines[3].ends_with(r#", "r", "100x30"]"#));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,96 @@
+/// Returns true when the cursor sits at the end of a shell prompt on the last
+/// non-empty row, i.e. the shell is idle waiting for a command.
+///
+/// The text before the cursor must end with one of `suffixes` (e.g. `"$ "`), and
+/// every row below the cursor must be blank.
+pub fn is_at_prompt(screen: &vt100::Screen, suffixes: &[String]) -> bool {
+    let (_rows, cols) = screen.size();
+    let (cursor_row, cursor_col) = screen.cursor_position();
+
+    let below_blank = screen
+        .rows(0, cols)
+        .skip(cursor_row as usize + 1)
+        .all(|line| line.trim().is_empty());
+    if !below_blank {
+        return false;
+    }
+
+    let before_cursor = screen.contents_between(cursor_row, 0, cursor_row, cursor_col);
+    // Cells the cursor skipped over without writing come back as nothing
+    let width = unicode_width::UnicodeWidthStr::width(before_cursor.as_str());
+    let padded = format!(
+        "{before_cursor}{}",
+        " ".repeat((cursor_col as usize).saturating_sub(width))
+    );
+    suffixes
+        .iter()
+        .any(|suffix| !suffix.is_empty() && padded.ends_with(suffix.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suffixes() -> Vec<String> {
+        ["$ ", "# ", "> ", "% ", "❯ "]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn screen(input: &[u8]) -> vt100::Parser {
+        let mut parser = vt100::Parser::new(10, 40, 0);
+        parser.process(input);
+        parser
+    }
+
+    #[test]
+    fn idle_bash_prompt() {
+        let parser = screen(b"user@host:~/proj$ ");
+        assert!(is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn idle_after_previous_output() {
+        let parser = screen(b"$ ls\r\nCargo.toml  src\r\n$ ");
+        assert!(is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn idle_starship_prompt() {
+        let parser = screen("~/proj\r\n❯ ".as_bytes());
+        assert!(is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn busy_while_command_prints() {
+        let parser = screen(b"$ cargo build\r\n   Compiling murmur");
+        assert!(!is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn busy_with_typed_command() {
+        let parser = screen(b"$ cargo test");
+        assert!(!is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn busy_when_output_below_cursor() {
+        // Cursor moved back up onto a prompt-looking row with output below it
+        let parser = screen(b"$ \r\nprogress 50%\x1b[1;3H");
+        assert!(!is_at_prompt(parser.screen(), &suffixes()));
+    }
+
+    #[test]
+    fn custom_suffix() {
+        let parser = screen(b"[proj] >>> ");
+        assert!(!is_at_prompt(parser.screen(), &["$ ".to_string()]));
+        assert!(is_at_prompt(parser.screen(), &[">>> ".to_string()]));
+    }
+
+    #[test]
+    fn empty_screen_is_not_a_prompt() {
+        let parser = screen(b"");
+        assert!(!is_at_prompt(parser.screen(), &suffixes()));
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::StartupSession;
+use crate::session::Session;
+
+/// Sessions open when murmur last quit, in the config's `[[sessions]]` format.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    #[serde(default)]
+    sessions: Vec<StartupSession>,
+}
+
+/// `<home>/.murmur/state.toml`, next to exports.
+pub fn state_path(home: &Path) -> PathBuf {
+    home.join(".murmur").join("state.toml")
+}
+
+/// What reopens each session: its current directory and command, plus its
+/// name when the user gave it one. Running processes can't be brought back.
+pub fn saved_sessions(sessions: &[Session]) -> Vec<StartupSession> {
+    sessions
+        .iter()
+        .map(|session| StartupSession {
+            path: session.current_dir().to_string_lossy().to_string(),
+            name: session.custom_name.then(|| session.name.clone()),
+            command: session.command().to_vec(),
+            env: session.env().clone(),
+        })
+        .collect()
+}
+
+pub fn save(path: &Path, sessions: &[StartupSession]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("{}", dir.display()))?;
+    }
+    let state = State {
+        sessions: sessions.to_vec(),
+    };
+    std::fs::write(path, toml::to_string(&state)?).with_context(|| format!("{}", path.display()))
+}
+
+/// The saved sessions, or none when nothing was saved yet.
+pub fn load(path: &Path) -> Result<Vec<StartupSession>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => {
+            let state: State =
+                toml::from_str(&text).with_context(|| format!("{}", path.display()))?;
+            Ok(state.sessions)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("{}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionSpec;
+    use std::collections::HashMap;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("murmur-state-{}-{name}", std::process::id()))
+            .join("state.toml")
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let path = temp_path("round-trip");
+        let sessions = vec![
+            StartupSession {
+                path: "/srv/api".into(),
+                name: Some("api".into()),
+                command: vec!["claude".into()],
+                env: HashMap::from([("RUST_LOG".into(), "debug".into())]),
+            },
+            StartupSession {
+                path: "/srv/web".into(),
+                name: None,
+                command: Vec::new(),
+                env: HashMap::new(),
+            },
+        ];
+        save(&path, &sessions).unwrap();
+        assert_eq!(load(&path).unwrap(), sessions);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn load_without_a_file_is_empty() {
+        assert!(load(&temp_path("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn saved_sessions_keep_command_and_custom_name() {
+        let dir = std::env::temp_dir();
+        let spec = |name: Option<&str>| SessionSpec {
+            cwd: dir.clone(),
+            command: vec!["cat".into()],
+            name: name.map(str::to_string),
+            env: HashMap::from([("A".into(), "1".into())]),
+        };
+        let sessions = [
+            Session::spawn_spec(&spec(Some("notes")), 24, 80).unwrap(),
+            Session::spawn_spec(&spec(None), 24, 80).unwrap(),
+        ];
+        let saved = saved_sessions(&sessions);
+        assert_eq!(saved[0].name.as_deref(), Some("notes"));
+        assert_eq!(saved[1].name, None);
+        assert_eq!(saved[1].command, ["cat"]);
+        assert_eq!(saved[1].env["A"], "1");
+        assert!(Path::new(&saved[1].path).is_dir());
+    }
+}
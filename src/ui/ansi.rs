@@ -1,5 +1,10 @@
-use std::io::Write;
-use unicode_width::UnicodeWidthChar;
+use std::io::{self, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::theme::{self, Glyphs, Theme};
+use crate::config::Truncate;
+use crate::key::keymap::Keymap;
+use crate::session::AiTool;
 
 /// Set DECSTBM scroll region to rows [top, bottom] (1-indexed).
 pub fn set_scroll_region(w: &mut impl Write, top: u16, bottom: u16) {
@@ -24,6 +29,38 @@ pub fn move_to(w: &mut impl Write, row: u16, col: u16) {
     write!(w, "\x1b[{};{}H", row, col).ok();
 }
 
+/// Ask the terminal to put `text` on the system clipboard (OSC 52). Works over
+/// SSH, since the terminal on the user's machine does the copying.
+pub fn osc52_copy(w: &mut impl Write, text: &str) {
+    write!(w, "\x1b]52;c;{}\x07", base64(text.as_bytes())).ok();
+}
+
+/// Ring the bell and ask the terminal to show `text` as a desktop notification
+/// (OSC 9). Terminals without OSC 9 support ignore it.
+pub fn bell_notify(w: &mut impl Write, text: &str) {
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    write!(w, "\x07\x1b]9;{text}\x07").ok();
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 /// Clear the entire line the cursor is on.
 fn clear_line(w: &mut impl Write) {
     write!(w, "\x1b[2K").ok();
@@ -57,86 +94,300 @@ pub(crate) fn truncate_to_width(s: &str, max_width: usize) -> &str {
     s
 }
 
-const RESET: &str = "\x1b[0m";
-const DIM: &str = "\x1b[90m";
-const CYAN: &str = "\x1b[36m";
-const YELLOW: &str = "\x1b[33m";
-const GREEN: &str = "\x1b[32m";
+/// Writer adapter for `--no-color`: drops color parameters from SGR sequences,
+/// keeping attributes like bold and reverse, and passes everything else through.
+/// Sequences may arrive split across writes.
+pub struct SgrFilter<'a, W: Write> {
+    inner: &'a mut W,
+    color: bool,
+    pending: Vec<u8>,
+}
+
+impl<'a, W: Write> SgrFilter<'a, W> {
+    pub fn new(inner: &'a mut W, color: bool) -> Self {
+        Self {
+            inner,
+            color,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for SgrFilter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.color {
+            return self.inner.write(buf);
+        }
+        let mut out = Vec::with_capacity(buf.len());
+        for &b in buf {
+            if self.pending.is_empty() {
+                if b == 0x1b {
+                    self.pending.push(b);
+                } else {
+                    out.push(b);
+                }
+                continue;
+            }
+            self.pending.push(b);
+            if self.pending.len() == 2 {
+                if b != b'[' {
+                    // Not a CSI sequence (e.g. ESC 7)
+                    out.append(&mut self.pending);
+                }
+            } else if (0x40..=0x7e).contains(&b) {
+                let seq = std::mem::take(&mut self.pending);
+                if b == b'm' {
+                    let params = String::from_utf8_lossy(&seq[2..seq.len() - 1]);
+                    if let Some(kept) = strip_sgr_colors(&params) {
+                        write!(out, "\x1b[{kept}m")?;
+                    }
+                } else {
+                    out.extend(seq);
+                }
+            }
+        }
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Remove foreground/background color parameters from an SGR parameter list.
+/// Returns `None` when nothing but colors was set, so the sequence can be dropped.
+fn strip_sgr_colors(params: &str) -> Option<String> {
+    let mut kept = Vec::new();
+    let mut it = params.split(';');
+    while let Some(p) = it.next() {
+        match p.parse::<u16>().unwrap_or(0) {
+            38 | 48 | 58 => match it.next() {
+                Some("5") => {
+                    it.next();
+                }
+                Some("2") => {
+                    it.nth(2);
+                }
+                _ => {}
+            },
+            30..=37 | 39 | 40..=47 | 49 | 90..=97 | 100..=107 => {}
+            _ => kept.push(p),
+        }
+    }
+    if kept.is_empty() && !params.is_empty() {
+        return None;
+    }
+    Some(kept.join(";"))
+}
+
+/// The last characters of `s` that fit within `max_width` display columns.
+fn tail_to_width(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in s.char_indices().rev() {
+        let cw = c.width().unwrap_or(0);
+        if width + cw > max_width {
+            return &s[i + c.len_utf8()..];
+        }
+        width += cw;
+    }
+    s
+}
+
+/// Fit `s` within `max_width` columns by cutting off the end and marking the
+/// cut with `ellipsis` ("start…").
+pub(crate) fn truncate_end(s: &str, max_width: usize, ellipsis: &str) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+    if max_width <= ellipsis.width() {
+        return truncate_to_width(s, max_width).to_string();
+    }
+    let head = truncate_to_width(s, max_width - ellipsis.width());
+    format!("{head}{ellipsis}")
+}
+
+/// Fit `s` within `max_width` columns by cutting out the middle ("start…end"),
+/// preferring to cut at spaces when one is close to the cut.
+pub(crate) fn truncate_middle(s: &str, max_width: usize, ellipsis: &str) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+    if max_width <= ellipsis.width() {
+        return truncate_to_width(s, max_width).to_string();
+    }
+    let budget = max_width - ellipsis.width();
+    let mut head = truncate_to_width(s, budget - budget / 2);
+    let mut tail = tail_to_width(s, budget / 2);
+    if let Some(p) = head.rfind(' ').filter(|&p| p >= head.len() / 2) {
+        head = &head[..p];
+    }
+    if let Some(q) = tail.find(' ').filter(|&q| q <= tail.len() / 2) {
+        tail = &tail[q + 1..];
+    }
+    format!("{head}{ellipsis}{tail}")
+}
+
+/// Break `line` at spaces into rows of at most `width` columns. A word wider
+/// than `width` gets a row of its own, left for the caller to truncate.
+pub(crate) fn wrap_words(line: &str, width: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut row = String::new();
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+        if !row.is_empty() && row.width() + 1 + word.width() > width {
+            rows.push(std::mem::take(&mut row));
+        }
+        if !row.is_empty() {
+            row.push(' ');
+        }
+        row.push_str(word);
+    }
+    rows.push(row);
+    rows
+}
+
+/// The pin bar rows for `prompt`: one per line, or word-wrapped to the bar's
+/// `cols - 4` text budget in `Truncate::Wrap` mode.
+pub(crate) fn pin_lines(prompt: &str, cols: u16, truncate: Truncate) -> Vec<String> {
+    let lines = prompt.split('\n');
+    match truncate {
+        Truncate::Wrap => {
+            let width = (cols as usize).saturating_sub(4).max(1);
+            lines.flat_map(|line| wrap_words(line, width)).collect()
+        }
+        Truncate::End | Truncate::Middle => lines.map(str::to_string).collect(),
+    }
+}
+
+pub(crate) const RESET: &str = "\x1b[0m";
 const BOLD: &str = "\x1b[1m";
+pub(crate) const REVERSE: &str = "\x1b[7m";
 const CLEAR_EOL: &str = "\x1b[K";
-const BAR_BG: &str = "\x1b[48;5;236m";
-const PREFIX_STYLE: &str = "\x1b[1;30;46m";
-const PREFIX_KEY_STYLE: &str = "\x1b[0;36m";
 
-fn render_separator(w: &mut impl Write, row: u16, cols: u16) {
-    move_to(w, row, 1);
-    let line = "\u{2500}".repeat(cols as usize);
-    write!(w, "{DIM}{line}{CLEAR_EOL}{RESET}").ok();
+/// The theme's colors as escape sequences, ready to write.
+struct Palette {
+    bg: String,
+    muted: String,
+    accent: String,
+    pin_marker: String,
+    pin_text: String,
+    note_marker: String,
+    ai_accent: String,
+    warning: String,
+    error: String,
+    update: String,
+    prefix: String,
+    prefix_keys: String,
 }
 
-pub fn render_bar_area(
-    w: &mut impl Write,
-    rows: u16,
-    bar_rows: u16,
-    cols: u16,
-    is_ai: bool,
-    pinned_prompt: &str,
-    position: Option<(usize, usize)>,
-) {
-    save_cursor(w);
-    let separator_row = rows.saturating_sub(bar_rows) + 1;
-    render_separator(w, separator_row, cols);
-    if is_ai {
-        render_pin_bar(w, separator_row + 1, cols, pinned_prompt, position);
+impl Palette {
+    fn new(theme: &Theme) -> Self {
+        Self {
+            bg: theme::sgr(&theme.background),
+            muted: theme::sgr(&theme.muted),
+            accent: theme::sgr(&theme.accent),
+            pin_marker: theme::sgr(&theme.pin_marker),
+            pin_text: theme::sgr(&theme.pin_text),
+            note_marker: theme::sgr(&theme.note_marker),
+            ai_accent: theme::sgr(&theme.ai_accent),
+            warning: theme::sgr(&theme.warning),
+            error: theme::sgr(&theme.error),
+            update: theme::sgr(&theme.update),
+            prefix: theme::sgr(&theme.prefix),
+            prefix_keys: theme::sgr(&theme.prefix_keys),
+        }
     }
-    restore_cursor(w);
 }
 
+/// Draw a horizontal rule across `row` in the theme's separator color.
+pub fn render_separator(w: &mut impl Write, row: u16, cols: u16, theme: &Theme, glyphs: &Glyphs) {
+    move_to(w, row, 1);
+    clear_line(w);
+    let color = theme::sgr(&theme.separator);
+    let line = glyphs
+        .rule
+        .repeat(cols as usize / glyphs.rule.width().max(1));
+    write!(w, "{color}{line}{CLEAR_EOL}{RESET}").ok();
+}
+
+/// What the pin bar shows.
+pub struct PinView<'a> {
+    pub prompt: &'a str,
+    pub position: Option<(usize, usize)>,
+    pub max_rows: usize,
+    pub truncate: Truncate,
+    /// The prompt is still being typed: drawn dimmed, without the history position.
+    pub draft: bool,
+}
+
+/// Draw the pinned prompt, one row per line up to `max_rows`. When lines are cut
+/// off, the last row notes how many more there are.
 pub fn render_pin_bar(
     w: &mut impl Write,
     start_row: u16,
     cols: u16,
-    pinned_prompt: &str,
-    position: Option<(usize, usize)>,
+    view: &PinView,
+    theme: &Theme,
+    glyphs: &Glyphs,
 ) {
+    let Palette {
+        bg,
+        muted,
+        pin_marker,
+        pin_text,
+        ..
+    } = &Palette::new(theme);
+    let PinView {
+        prompt: pinned_prompt,
+        position,
+        max_rows,
+        truncate,
+        draft,
+    } = *view;
+    let marker = &glyphs.marker;
+    let text_style = if draft { muted } else { pin_text };
     if pinned_prompt.is_empty() {
         move_to(w, start_row, 1);
         clear_line(w);
-        write!(w, "{BAR_BG}{DIM} \u{258e} (no prompt){CLEAR_EOL}{RESET}").ok();
+        write!(w, "{bg}{muted} {marker} (no prompt){CLEAR_EOL}{RESET}").ok();
     } else {
-        let indicator = match position {
+        let indicator = match position.filter(|_| !draft) {
             Some((cur, total)) => format!("[{}/{}] ", cur, total),
             None => String::new(),
         };
-        let indicator_width = indicator.len();
-        let available = (cols as usize).saturating_sub(4 + indicator_width);
-        for (i, line) in pinned_prompt.split('\n').enumerate() {
+        let indicator_width = indicator.width();
+        let max_rows = max_rows.max(1);
+        let lines = pin_lines(pinned_prompt, cols, truncate);
+        let hidden = lines.len().saturating_sub(max_rows);
+        for (i, line) in lines.iter().take(max_rows).enumerate() {
             let row = start_row + i as u16;
             move_to(w, row, 1);
             clear_line(w);
 
-            let truncated = truncate_to_width(line, available);
-            let display = if truncated.len() < line.len() {
-                format!(
-                    "{}...",
-                    truncate_to_width(line, available.saturating_sub(3))
-                )
+            let more = if hidden > 0 && i + 1 == max_rows {
+                format!(" (+{hidden} lines)")
             } else {
-                truncated.to_string()
+                String::new()
+            };
+            let available =
+                (cols as usize).saturating_sub(3 + marker.width() + indicator_width + more.width());
+            let display = match truncate {
+                Truncate::Middle => truncate_middle(line, available, &glyphs.ellipsis),
+                Truncate::End | Truncate::Wrap => truncate_end(line, available, &glyphs.ellipsis),
             };
 
             if i == 0 && !indicator.is_empty() {
                 write!(
                     w,
-                    "{BAR_BG}{CYAN} \u{258e}{RESET}{BAR_BG} {DIM}{}{YELLOW}{}{CLEAR_EOL}{RESET}",
-                    indicator, display
+                    "{bg}{pin_marker} {marker}{RESET}{bg} {muted}{}{text_style}{}{muted}{}{CLEAR_EOL}{RESET}",
+                    indicator, display, more
                 )
                 .ok();
             } else {
                 write!(
                     w,
-                    "{BAR_BG}{CYAN} \u{258e}{RESET}{BAR_BG} {YELLOW}{}{CLEAR_EOL}{RESET}",
-                    display
+                    "{bg}{pin_marker} {marker}{RESET}{bg} {text_style}{}{muted}{}{CLEAR_EOL}{RESET}",
+                    display, more
                 )
                 .ok();
             }
@@ -144,61 +395,441 @@ pub fn render_pin_bar(
     }
 }
 
-pub fn render_hint_bar(
+/// Draw a session's sticky note on `row`.
+pub fn render_note_bar(
     w: &mut impl Write,
     row: u16,
-    prefix_armed: bool,
-    window_title: &str,
-    update_version: Option<&str>,
+    cols: u16,
+    note: &str,
+    theme: &Theme,
+    glyphs: &Glyphs,
 ) {
+    let Palette {
+        bg, note_marker, ..
+    } = &Palette::new(theme);
+    let marker = &glyphs.marker;
     move_to(w, row, 1);
     clear_line(w);
+    let available = (cols as usize).saturating_sub(2 + marker.width());
+    let display = truncate_end(note, available, &glyphs.ellipsis);
+    write!(
+        w,
+        "{bg}{note_marker} {marker}{RESET}{bg} {BOLD}{display}{CLEAR_EOL}{RESET}"
+    )
+    .ok();
+}
 
-    if prefix_armed {
-        let update_hint = if update_version.is_some() {
-            "  u: update"
-        } else {
-            ""
-        };
-        write!(
-            w,
-            "{PREFIX_STYLE} Ctrl+\\ {PREFIX_KEY_STYLE} x: unpin{update_hint}  q: quit {RESET}"
-        )
-        .ok();
+/// Draw a text prompt (`label: text`) on `row`, replacing the hint bar while typing.
+pub fn render_input_line(
+    w: &mut impl Write,
+    row: u16,
+    label: &str,
+    text: &str,
+    hint: &str,
+    theme: &Theme,
+) {
+    let Palette {
+        bg, accent, muted, ..
+    } = &Palette::new(theme);
+    move_to(w, row, 1);
+    clear_line(w);
+    write!(
+        w,
+        "{bg}{accent} {label}: {RESET}{bg}{text}{muted}{hint}{CLEAR_EOL}{RESET}"
+    )
+    .ok();
+}
+
+/// Loop iterations per spinner step: about 100ms at the 16ms event poll.
+pub const SPINNER_STEP: u64 = 6;
+
+/// Spinner frame for a loop iteration.
+fn spinner_phase(frame: u64) -> u64 {
+    frame / SPINNER_STEP
+}
+
+/// Session state shown in the hint bar.
+pub struct Hint<'a> {
+    pub cols: u16,
+    pub prefix_armed: bool,
+    pub window_title: &'a str,
+    pub update_version: Option<&'a str>,
+    pub paused: bool,
+    pub recording: bool,
+    /// Typed input goes to every session.
+    pub broadcast: bool,
+    pub message: Option<&'a str>,
+    pub session_name: &'a str,
+    /// `Some((1-based index, total))` when more than one session is open.
+    pub session_position: Option<(usize, usize)>,
+    /// 1-based positions of other sessions with output not yet seen.
+    pub active_sessions: &'a [usize],
+    pub cwd: Option<&'a str>,
+    /// The AI tool running in the session.
+    pub ai_tool: Option<&'a AiTool>,
+    /// The session's working directory has been removed.
+    pub cwd_missing: bool,
+    /// The program has not written anything yet.
+    pub starting: bool,
+    /// Exit code once the session's program has exited.
+    pub exited: Option<u32>,
+    /// Running time, or total time once exited.
+    pub uptime: Option<&'a str>,
+    /// Time since the last output, shown once it is long enough to matter.
+    pub quiet: Option<&'a str>,
+    /// The view is scrolled back from the live screen.
+    pub scrolled: bool,
+    /// Copy mode is active.
+    pub copying: bool,
+    /// `Some((1-based current match, total))` while stepping through search results.
+    pub search: Option<(usize, usize)>,
+    /// Some output was dropped because murmur fell behind.
+    pub dropped: bool,
+    /// Loop iterations since startup; drives the busy spinner.
+    pub frame: u64,
+    /// `Some(true)` when a shell or AI tool is idle waiting for input, `Some(false)`
+    /// while busy, `None` when unknown.
+    pub at_prompt: Option<bool>,
+    /// Source of the key list shown while the prefix is armed.
+    pub keymap: &'a Keymap,
+    pub theme: &'a Theme,
+    pub glyphs: &'a Glyphs,
+}
+
+pub fn render_hint_bar(w: &mut impl Write, row: u16, hint: &Hint) {
+    let Glyphs {
+        divider,
+        arrow,
+        update,
+        paused,
+        warning,
+        recording,
+        ..
+    } = hint.glyphs;
+    let Palette {
+        bg,
+        muted,
+        accent,
+        ai_accent,
+        warning: attention,
+        error,
+        update: update_color,
+        prefix,
+        prefix_keys,
+        ..
+    } = &Palette::new(hint.theme);
+    move_to(w, row, 1);
+    clear_line(w);
+
+    if hint.prefix_armed {
+        let keys = hint.keymap.hint(hint.update_version.is_some());
+        // " Ctrl+\ " label, spaces around the keys, and the last column left free
+        let available = (hint.cols as usize).saturating_sub(11);
+        let keys = truncate_end(&keys, available, &hint.glyphs.ellipsis);
+        write!(w, "{prefix} Ctrl+\\ {prefix_keys} {keys} {RESET}").ok();
     } else {
-        write!(w, "{BAR_BG}").ok();
+        let mut segments = Vec::new();
+
+        if let Some(message) = hint.message {
+            segments.push(
+                Segment::new(
+                    0,
+                    [
+                        (error.clone(), message.to_string()),
+                        (muted.clone(), format!(" {divider} ")),
+                    ],
+                )
+                .shrink(truncate_end),
+            );
+        }
+
+        if let Some((cur, total)) = hint.session_position {
+            segments.push(Segment::new(
+                0,
+                [
+                    (
+                        accent.clone(),
+                        format!("[{cur}/{total}] {}", hint.session_name),
+                    ),
+                    (muted.clone(), format!(" {divider} ")),
+                ],
+            ));
+        }
+
+        if !hint.active_sessions.is_empty() {
+            let active: Vec<String> = hint.active_sessions.iter().map(usize::to_string).collect();
+            segments.push(Segment::new(
+                0,
+                [
+                    (attention.clone(), format!("*{}", active.join(","))),
+                    (muted.clone(), format!(" {divider} ")),
+                ],
+            ));
+        }
+
+        if let Some(cwd) = hint.cwd {
+            // Cut from the middle so the innermost directory stays readable
+            let cwd = truncate_middle(cwd, hint.cols as usize / 3, &hint.glyphs.ellipsis);
+            segments.push(
+                Segment::new(
+                    0,
+                    [
+                        (muted.clone(), cwd),
+                        (String::new(), format!(" {divider} ")),
+                    ],
+                )
+                .shrink(truncate_middle),
+            );
+        }
+
+        if let Some(tool) = hint.ai_tool {
+            let glyph = if tool.glyph.is_empty() {
+                String::new()
+            } else {
+                format!("{} ", tool.glyph)
+            };
+            segments.push(Segment::new(
+                0,
+                [
+                    (ai_accent.clone(), format!("{glyph}{}", tool.name)),
+                    (muted.clone(), format!(" {divider} ")),
+                ],
+            ));
+        }
+
+        let marker =
+            |color: &str, text: &str| Segment::new(0, [(color.to_string(), text.to_string())]);
+        let bold = format!("{BOLD}{attention}");
+
+        if hint.broadcast {
+            segments.push(marker(&bold, "[SYNC] "));
+        }
+
+        if hint.copying {
+            segments.push(Segment::new(
+                0,
+                [
+                    (bold.clone(), "[COPY]".to_string()),
+                    (
+                        format!("{RESET}{bg}{muted}"),
+                        " v: select  y: copy  q: exit ".to_string(),
+                    ),
+                ],
+            ));
+        } else if let Some((cur, total)) = hint.search {
+            segments.push(Segment::new(
+                0,
+                [
+                    (bold.clone(), format!("[SEARCH {cur}/{total}]")),
+                    (
+                        format!("{RESET}{bg}{muted}"),
+                        " n: older  N: newer  q: exit ".to_string(),
+                    ),
+                ],
+            ));
+        } else if hint.scrolled {
+            segments.push(marker(&bold, "[SCROLL] "));
+        }
+
+        if hint.recording {
+            segments.push(marker(error, &format!("{recording} REC ")));
+        }
+
+        if hint.paused {
+            segments.push(marker(attention, &format!("{paused} paused ")));
+        }
+
+        if hint.dropped {
+            segments.push(marker(attention, &format!("{warning} output dropped ")));
+        }
+
+        if hint.starting {
+            segments.push(marker(attention, "starting "));
+        }
+
+        match hint.exited {
+            Some(0) => segments.push(marker(muted, "exited ")),
+            Some(code) => segments.push(marker(error, &format!("exited ({code}) "))),
+            None => {}
+        }
+
+        if hint.cwd_missing {
+            segments.push(marker(attention, "cwd gone "));
+        }
+
+        match hint.at_prompt {
+            Some(true) => segments.push(marker(muted, "idle ")),
+            Some(false) => {
+                let spin = hint.glyphs.spinner_frame(spinner_phase(hint.frame));
+                segments.push(marker(attention, &format!("{spin} busy ")));
+            }
+            None => {}
+        }
+
+        // The rest is dropped when the line runs out of room, the title first
+        if let Some(uptime) = hint.uptime {
+            let verb = if hint.exited.is_some() { "ran" } else { "up" };
+            segments.push(Segment::new(
+                3,
+                [(muted.clone(), format!("{verb} {uptime} "))],
+            ));
+        }
+
+        if let Some(quiet) = hint.quiet {
+            segments.push(Segment::new(
+                4,
+                [(muted.clone(), format!("quiet {quiet} "))],
+            ));
+        }
 
-        if !window_title.is_empty() {
-            write!(w, "{DIM}{}{RESET}{BAR_BG}", window_title).ok();
+        if !hint.window_title.is_empty() {
+            segments.push(
+                Segment::new(5, [(muted.clone(), hint.window_title.to_string())])
+                    .shrink(truncate_end),
+            );
         }
 
-        write!(
-            w,
-            "{DIM} \u{2502} {CYAN}Ctrl+\\{DIM} \u{2192} q{RESET}{BAR_BG}"
-        )
-        .ok();
+        segments.push(Segment::new(
+            1,
+            [
+                (muted.clone(), format!(" {divider} ")),
+                (accent.clone(), "Ctrl+\\".to_string()),
+                (muted.clone(), format!(" {arrow} q")),
+            ],
+        ));
 
         let current = env!("CARGO_PKG_VERSION");
-        if let Some(ver) = update_version {
-            write!(
-                w,
-                "{DIM} \u{2502} v{current} {GREEN}\u{2191} v{ver}{RESET}{BAR_BG}"
-            )
-            .ok();
-        } else {
-            write!(w, "{DIM} \u{2502} v{current}{RESET}{BAR_BG}").ok();
+        let mut version = vec![(muted.clone(), format!(" {divider} v{current}"))];
+        if let Some(ver) = hint.update_version {
+            version.push((update_color.clone(), format!(" {update} v{ver}")));
         }
+        segments.push(Segment::new(2, version));
 
+        // The last column stays free so the terminal never wraps the bar
+        let mut room = (hint.cols as usize).saturating_sub(1);
+        fit_segments(&mut segments, room, &hint.glyphs.ellipsis);
+        write!(w, "{bg}").ok();
+        for segment in &segments {
+            for (codes, text) in &segment.runs {
+                let text = truncate_to_width(text, room);
+                room -= text.width();
+                write!(w, "{codes}{text}").ok();
+            }
+            write!(w, "{RESET}{bg}").ok();
+        }
         write!(w, "{CLEAR_EOL}{RESET}").ok();
     }
 }
 
-pub fn render_update_message(w: &mut impl Write, row: u16, version: &str) {
+/// A piece of the normal hint bar, as runs of escape codes and the text they color.
+struct Segment {
+    runs: Vec<(String, String)>,
+    /// While the line is too wide, segments go highest rank first. Rank 0 stays.
+    rank: u8,
+    /// Cuts the first run's text to make room before the segment is dropped.
+    shrink: Option<fn(&str, usize, &str) -> String>,
+}
+
+impl Segment {
+    /// Narrowest a ranked segment is shortened to; below that it is dropped.
+    const MIN_WIDTH: usize = 10;
+
+    fn new(rank: u8, runs: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            runs: runs.into_iter().collect(),
+            rank,
+            shrink: None,
+        }
+    }
+
+    fn shrink(mut self, cut: fn(&str, usize, &str) -> String) -> Self {
+        self.shrink = Some(cut);
+        self
+    }
+
+    fn width(&self) -> usize {
+        self.runs.iter().map(|(_, text)| text.width()).sum()
+    }
+
+    /// Give up `excess` columns from the first run, keeping at least `min`.
+    fn shorten(&mut self, excess: usize, min: usize, ellipsis: &str) -> bool {
+        let (Some(cut), Some((_, text))) = (self.shrink, self.runs.first_mut()) else {
+            return false;
+        };
+        let width = text.width();
+        if width < excess + min {
+            return false;
+        }
+        *text = cut(text, width - excess, ellipsis);
+        true
+    }
+}
+
+/// Drop and shorten `segments` until they fit in `room` columns.
+fn fit_segments(segments: &mut Vec<Segment>, room: usize, ellipsis: &str) {
+    let excess = |segments: &[Segment]| {
+        segments
+            .iter()
+            .map(Segment::width)
+            .sum::<usize>()
+            .saturating_sub(room)
+    };
+    while excess(segments) > 0 {
+        let Some(i) = (0..segments.len())
+            .filter(|&i| segments[i].rank > 0)
+            .max_by_key(|&i| segments[i].rank)
+        else {
+            break;
+        };
+        let over = excess(segments);
+        if !segments[i].shorten(over, Segment::MIN_WIDTH, ellipsis) {
+            segments.remove(i);
+        }
+    }
+    // Then the cwd, before an error message loses any of its words. One too
+    // narrow to read is dropped; whatever is left over is clipped when written.
+    for i in (0..segments.len()).rev() {
+        let over = excess(segments);
+        if over == 0 {
+            break;
+        }
+        if segments[i].shrink.is_some() && !segments[i].shorten(over, Segment::MIN_WIDTH, ellipsis)
+        {
+            segments.remove(i);
+        }
+    }
+}
+
+pub fn render_update_message(w: &mut impl Write, row: u16, version: &str, theme: &Theme) {
+    let Palette { bg, update, .. } = &Palette::new(theme);
+    move_to(w, row, 1);
+    clear_line(w);
+    write!(
+        w,
+        "{bg}{update} Update to v{version}: {BOLD}npm i -g @steadymoka/murmur{CLEAR_EOL}{RESET}"
+    )
+    .ok();
+}
+
+/// Show the focused session's process id and working directory in the hint bar row.
+pub fn render_session_info(
+    w: &mut impl Write,
+    row: u16,
+    pid: Option<u32>,
+    cwd: &str,
+    theme: &Theme,
+    glyphs: &Glyphs,
+) {
+    let Palette {
+        bg, muted, accent, ..
+    } = &Palette::new(theme);
     move_to(w, row, 1);
     clear_line(w);
+    let pid = pid.map_or_else(|| "?".to_string(), |p| p.to_string());
+    let divider = &glyphs.divider;
     write!(
         w,
-        "{BAR_BG}{GREEN} Update to v{version}: {BOLD}npm i -g @steadymoka/murmur{CLEAR_EOL}{RESET}"
+        "{bg}{muted} pid {accent}{pid}{muted} {divider} {cwd}{CLEAR_EOL}{RESET}"
     )
     .ok();
 }
@@ -206,11 +837,42 @@ pub fn render_update_message(w: &mut impl Write, row: u16, version: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::LazyLock;
 
     fn output(buf: &[u8]) -> String {
         String::from_utf8_lossy(buf).to_string()
     }
 
+    #[test]
+    fn test_strip_sgr_colors() {
+        assert_eq!(strip_sgr_colors("1;30;46").as_deref(), Some("1"));
+        assert_eq!(strip_sgr_colors("0;36").as_deref(), Some("0"));
+        assert_eq!(strip_sgr_colors("48;5;236"), None);
+        assert_eq!(strip_sgr_colors("38;2;1;2;3;7").as_deref(), Some("7"));
+        assert_eq!(strip_sgr_colors("").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_sgr_filter_split_writes() {
+        let mut buf = Vec::new();
+        {
+            let mut w = SgrFilter::new(&mut buf, false);
+            w.write_all(b"\x1b[1;3").unwrap();
+            w.write_all(b"6mhi\x1b").unwrap();
+            w.write_all(b"[24;1H\x1b7\x1b[90mx\x1b[0m").unwrap();
+        }
+        assert_eq!(output(&buf), "\x1b[1mhi\x1b[24;1H\x1b7x\x1b[0m");
+    }
+
+    #[test]
+    fn test_sgr_filter_passthrough_with_color() {
+        let mut buf = Vec::new();
+        SgrFilter::new(&mut buf, true)
+            .write_all(b"\x1b[36mhi")
+            .unwrap();
+        assert_eq!(output(&buf), "\x1b[36mhi");
+    }
+
     #[test]
     fn test_set_scroll_region() {
         let mut buf = Vec::new();
@@ -282,16 +944,235 @@ mod tests {
     #[test]
     fn test_render_separator() {
         let mut buf = Vec::new();
-        render_separator(&mut buf, 10, 5);
+        render_separator(&mut buf, 10, 5, &Theme::default(), &Glyphs::default());
         let s = output(&buf);
         assert!(s.contains("\x1b[10;1H")); // move to row 10
         assert!(s.contains("\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}")); // 5 horizontal lines
     }
 
+    #[test]
+    fn test_render_separator_wide_rule() {
+        let mut buf = Vec::new();
+        let glyphs = Glyphs {
+            rule: "\u{2550}\u{2550}".into(),
+            ..Glyphs::default()
+        };
+        render_separator(&mut buf, 10, 5, &Theme::default(), &glyphs);
+        // 5 columns fit two copies of a 2-column rule
+        assert_eq!(output(&buf).matches('\u{2550}').count(), 4);
+    }
+
+    #[test]
+    fn test_render_separator_theme_color() {
+        let mut buf = Vec::new();
+        let theme = Theme {
+            separator: "38;5;240".into(),
+            ..Theme::default()
+        };
+        render_separator(&mut buf, 10, 5, &theme, &Glyphs::default());
+        assert!(output(&buf).contains("\x1b[38;5;240m\u{2500}"));
+    }
+
+    fn pin(prompt: &str, position: Option<(usize, usize)>) -> PinView<'_> {
+        PinView {
+            prompt,
+            position,
+            max_rows: 4,
+            truncate: Truncate::End,
+            draft: false,
+        }
+    }
+
+    #[test]
+    fn test_truncate_middle_fits() {
+        assert_eq!(truncate_middle("short", 10, "\u{2026}"), "short");
+    }
+
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("short", 5, "\u{2026}"), "short");
+        assert_eq!(truncate_end("shorter", 5, "\u{2026}"), "shor\u{2026}");
+        assert_eq!(truncate_end("shorter", 5, "..."), "sh...");
+        // 8 columns from 4 characters; the cut never splits one
+        assert_eq!(
+            truncate_end("\u{5B9F}\u{88C5}\u{3057}\u{3066}", 6, "\u{2026}"),
+            "\u{5B9F}\u{88C5}\u{2026}"
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_both_ends() {
+        let s = "please refactor the session module and then tell me why tests fail";
+        let out = truncate_middle(s, 30, "...");
+        assert!(out.width() <= 30, "{out}");
+        assert!(out.starts_with("please "));
+        assert!(out.ends_with("tests fail"));
+        assert!(out.contains("..."));
+    }
+
+    #[test]
+    fn test_truncate_middle_wide_chars() {
+        let s = "\u{4F60}".repeat(20);
+        let out = truncate_middle(&s, 11, "\u{2026}");
+        assert!(out.width() <= 11);
+        assert!(out.starts_with('\u{4F60}') && out.ends_with('\u{4F60}'));
+    }
+
+    #[test]
+    fn test_render_pin_bar_middle_truncation() {
+        let mut buf = Vec::new();
+        let prompt = format!("start {} the actual question?", "x".repeat(100));
+        let view = PinView {
+            truncate: Truncate::Middle,
+            ..pin(&prompt, None)
+        };
+        render_pin_bar(
+            &mut buf,
+            10,
+            40,
+            &view,
+            &Theme::default(),
+            &Glyphs::default(),
+        );
+        let s = output(&buf);
+        assert!(s.contains("start"));
+        assert!(s.contains("question?"));
+    }
+
+    #[test]
+    fn test_wrap_words() {
+        assert_eq!(wrap_words("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap_words("one two three", 80), ["one two three"]);
+        assert_eq!(wrap_words("", 10), [""]);
+        // A word wider than the row keeps a row of its own
+        assert_eq!(
+            wrap_words("go abcdefghijkl now", 8),
+            ["go", "abcdefghijkl", "now"]
+        );
+    }
+
+    #[test]
+    fn test_render_pin_bar_wraps() {
+        let mut buf = Vec::new();
+        let view = PinView {
+            truncate: Truncate::Wrap,
+            ..pin("fix the flaky test in session\nthen rerun", None)
+        };
+        render_pin_bar(
+            &mut buf,
+            10,
+            20,
+            &view,
+            &Theme::default(),
+            &Glyphs::default(),
+        );
+        let s = output(&buf);
+        assert!(s.contains("fix the flaky"));
+        assert!(s.contains("test in session"));
+        assert!(s.contains("then rerun"));
+        assert!(!s.contains('\u{2026}'));
+        assert!(s.contains("\x1b[12;1H"));
+    }
+
+    /// Columns the text of `s` takes once escape sequences are removed.
+    fn visible_width(s: &str) -> usize {
+        let mut text = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                if chars.next() == Some('[') {
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+            } else {
+                text.push(c);
+            }
+        }
+        text.width()
+    }
+
+    #[test]
+    fn test_render_pin_bar_wide_prompt_fits() {
+        let prompt = "\u{1F600}\u{5B9F}".repeat(50); // 200 columns
+        for truncate in [Truncate::End, Truncate::Middle, Truncate::Wrap] {
+            let mut buf = Vec::new();
+            let view = PinView {
+                truncate,
+                ..pin(&prompt, Some((3, 12)))
+            };
+            render_pin_bar(
+                &mut buf,
+                10,
+                80,
+                &view,
+                &Theme::default(),
+                &Glyphs::default(),
+            );
+            let s = output(&buf);
+            assert!(s.contains('\u{1F600}'));
+            assert!(
+                visible_width(&s) <= 80,
+                "{truncate:?}: {}",
+                visible_width(&s)
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_pin_bar_draft() {
+        let mut buf = Vec::new();
+        let view = PinView {
+            draft: true,
+            ..pin("half-typed", Some((2, 5)))
+        };
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &view,
+            &Theme::default(),
+            &Glyphs::default(),
+        );
+        let s = output(&buf);
+        assert!(s.contains("\x1b[90mhalf-typed"));
+        assert!(!s.contains("[2/5]"));
+    }
+
+    #[test]
+    fn test_render_pin_bar_theme_colors() {
+        let theme = Theme {
+            pin_marker: "35".into(),
+            pin_text: "38;5;214".into(),
+            ..Theme::default()
+        };
+        let mut buf = Vec::new();
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin("ship it", None),
+            &theme,
+            &Glyphs::default(),
+        );
+        let s = output(&buf);
+        assert!(s.contains("\x1b[35m \u{258e}"));
+        assert!(s.contains("\x1b[38;5;214mship it"));
+    }
+
     #[test]
     fn test_render_pin_bar_empty() {
         let mut buf = Vec::new();
-        render_pin_bar(&mut buf, 10, 80, "", None);
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin("", None),
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
         assert!(s.contains("(no prompt)"));
     }
@@ -299,7 +1180,14 @@ mod tests {
     #[test]
     fn test_render_pin_bar_with_content() {
         let mut buf = Vec::new();
-        render_pin_bar(&mut buf, 10, 80, "test prompt", None);
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin("test prompt", None),
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
         assert!(s.contains("test prompt"));
         assert!(s.contains("\u{258e}")); // left bar char
@@ -308,7 +1196,14 @@ mod tests {
     #[test]
     fn test_render_pin_bar_with_position() {
         let mut buf = Vec::new();
-        render_pin_bar(&mut buf, 10, 80, "prompt", Some((2, 5)));
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin("prompt", Some((2, 5))),
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
         assert!(s.contains("[2/5]"));
         assert!(s.contains("prompt"));
@@ -317,7 +1212,14 @@ mod tests {
     #[test]
     fn test_render_pin_bar_multiline() {
         let mut buf = Vec::new();
-        render_pin_bar(&mut buf, 10, 80, "line1\nline2", None);
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin("line1\nline2", None),
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
         assert!(s.contains("line1"));
         assert!(s.contains("line2"));
@@ -326,10 +1228,81 @@ mod tests {
     }
 
     #[test]
-    fn test_render_hint_bar_normal() {
+    fn test_render_pin_bar_caps_rows() {
         let mut buf = Vec::new();
-        render_hint_bar(&mut buf, 24, false, "my-title", None);
+        let prompt = (1..=50)
+            .map(|i| format!("l{i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        render_pin_bar(
+            &mut buf,
+            10,
+            80,
+            &pin(&prompt, None),
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
+        assert!(s.contains("\x1b[13;1H"));
+        assert!(!s.contains("\x1b[14;1H"));
+        assert!(s.contains("l4"));
+        assert!(!s.contains("l5"));
+        assert!(s.contains("(+46 lines)"));
+    }
+
+    static THEME: LazyLock<Theme> = LazyLock::new(Theme::default);
+    static KEYMAP: LazyLock<Keymap> = LazyLock::new(Keymap::default);
+
+    fn plain_hint(glyphs: &Glyphs) -> Hint<'_> {
+        Hint {
+            cols: 200,
+            prefix_armed: false,
+            window_title: "",
+            update_version: None,
+            paused: false,
+            recording: false,
+            broadcast: false,
+            message: None,
+            session_name: "",
+            session_position: None,
+            active_sessions: &[],
+            cwd: None,
+            ai_tool: None,
+            cwd_missing: false,
+            starting: false,
+            exited: None,
+            uptime: None,
+            quiet: None,
+            scrolled: false,
+            copying: false,
+            search: None,
+            dropped: false,
+            frame: 0,
+            at_prompt: None,
+            keymap: &KEYMAP,
+            theme: &THEME,
+            glyphs,
+        }
+    }
+
+    fn render_hint(hint: &Hint) -> String {
+        let mut buf = Vec::new();
+        render_hint_bar(&mut buf, 24, hint);
+        output(&buf)
+    }
+
+    fn hint(prefix_armed: bool, window_title: &str, update_version: Option<&str>) -> String {
+        render_hint(&Hint {
+            prefix_armed,
+            window_title,
+            update_version,
+            ..plain_hint(&Glyphs::default())
+        })
+    }
+
+    #[test]
+    fn test_render_hint_bar_normal() {
+        let s = hint(false, "my-title", None);
         assert!(s.contains("my-title"));
         assert!(s.contains("Ctrl+\\"));
         let current = env!("CARGO_PKG_VERSION");
@@ -338,49 +1311,394 @@ mod tests {
 
     #[test]
     fn test_render_hint_bar_prefix_armed() {
-        let mut buf = Vec::new();
-        render_hint_bar(&mut buf, 24, true, "", None);
-        let s = output(&buf);
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            cols: 400,
+            prefix_armed: true,
+            ..plain_hint(&glyphs)
+        });
         assert!(s.contains("x: unpin"));
+        assert!(s.contains("m: note"));
+        assert!(s.contains("i: info"));
+        assert!(s.contains("s: pause"));
         assert!(s.contains("q: quit"));
+        assert!(s.contains("Ctrl+\\: send Ctrl+\\"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_prefix_armed_fits_width() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            cols: 40,
+            prefix_armed: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(visible_width(&s) <= 39, "{}", visible_width(&s));
+        assert!(s.contains("pins"));
+        assert!(s.contains('\u{2026}'));
     }
 
     #[test]
     fn test_render_hint_bar_with_update() {
-        let mut buf = Vec::new();
-        render_hint_bar(&mut buf, 24, false, "", Some("0.2.0"));
-        let s = output(&buf);
+        let s = hint(false, "", Some("0.2.0"));
         let current = env!("CARGO_PKG_VERSION");
         assert!(s.contains(&format!("v{current}")));
         assert!(s.contains("v0.2.0"));
     }
 
     #[test]
-    fn test_render_update_message() {
+    fn test_render_hint_bar_paused() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            window_title: "title",
+            paused: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\u{23f8} paused"));
+        assert!(!hint(false, "title", None).contains("paused"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_recording() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            window_title: "title",
+            recording: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\u{25cf} REC"));
+        assert!(!hint(false, "title", None).contains("REC"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_broadcast() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            broadcast: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("[SYNC]"));
+        assert!(!hint(false, "title", None).contains("[SYNC]"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_runtime() {
+        let glyphs = Glyphs::default();
+        let running = render_hint(&Hint {
+            uptime: Some("12m"),
+            quiet: Some("3m"),
+            ..plain_hint(&glyphs)
+        });
+        assert!(running.contains("up 12m"));
+        assert!(running.contains("quiet 3m"));
+        let exited = render_hint(&Hint {
+            uptime: Some("1h5m"),
+            exited: Some(0),
+            ..plain_hint(&glyphs)
+        });
+        assert!(exited.contains("ran 1h5m"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_fits_width() {
+        let glyphs = Glyphs::default();
+        let tool = AiTool {
+            pattern: "claude".into(),
+            name: "Claude Code".into(),
+            glyph: "\u{2726}".into(),
+        };
+        let full = Hint {
+            cols: 80,
+            session_name: "api",
+            session_position: Some((1, 3)),
+            active_sessions: &[2],
+            cwd: Some("~/work/api"),
+            ai_tool: Some(&tool),
+            at_prompt: Some(false),
+            uptime: Some("1h5m"),
+            quiet: Some("3m"),
+            window_title: "claude: refactoring the session manager",
+            ..plain_hint(&glyphs)
+        };
+        let s = render_hint(&full);
+        assert!(visible_width(&s) <= 79, "{}", visible_width(&s));
+        assert!(s.contains("busy") && s.contains("~/work/api"));
+        assert!(!s.contains("refactoring the session manager"));
+
+        let s = render_hint(&Hint { cols: 30, ..full });
+        assert!(visible_width(&s) <= 29, "{}", visible_width(&s));
+        assert!(s.contains("[1/3] api"));
+        assert!(!s.contains("quiet") && !s.contains("up 1h5m"));
+        assert!(!s.contains("~/work"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_shell_state() {
+        let glyphs = Glyphs::default();
+        let render = |at_prompt| {
+            render_hint(&Hint {
+                at_prompt,
+                ..plain_hint(&glyphs)
+            })
+        };
+        assert!(render(Some(true)).contains("idle"));
+        assert!(render(Some(false)).contains("\u{280b} busy"));
+        let none = render(None);
+        assert!(!none.contains("idle") && !none.contains("busy"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_message_and_sessions() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            message: Some("not a directory: ~/nope"),
+            session_name: "api",
+            session_position: Some((2, 3)),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\x1b[31mnot a directory: ~/nope"));
+        assert!(s.contains("[2/3] api"));
+        assert!(!s.contains('*'));
+        let s = render_hint(&Hint {
+            session_position: Some((2, 3)),
+            active_sessions: &[1, 3],
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\x1b[33m*1,3"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_starting() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            starting: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("starting"));
+        assert!(!hint(false, "", None).contains("starting"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_exited() {
+        let glyphs = Glyphs::default();
+        let failed = render_hint(&Hint {
+            exited: Some(2),
+            ..plain_hint(&glyphs)
+        });
+        assert!(failed.contains("\x1b[31mexited (2)"));
+        let clean = render_hint(&Hint {
+            exited: Some(0),
+            ..plain_hint(&glyphs)
+        });
+        assert!(clean.contains("exited"));
+        assert!(!clean.contains("(0)"));
+        assert!(!hint(false, "", None).contains("exited"));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("héllo\n".as_bytes()), "aMOpbGxvCg==");
+    }
+
+    #[test]
+    fn test_bell_notify() {
+        let mut buf = Vec::new();
+        bell_notify(&mut buf, "api finished\x1b");
+        assert_eq!(output(&buf), "\x07\x1b]9;api finished\x07");
+    }
+
+    #[test]
+    fn test_osc52_copy() {
         let mut buf = Vec::new();
-        render_update_message(&mut buf, 24, "0.3.0");
+        osc52_copy(&mut buf, "foo");
+        assert_eq!(buf, b"\x1b]52;c;Zm9v\x07");
+    }
+
+    #[test]
+    fn test_render_hint_bar_scrolled() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            scrolled: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("[SCROLL]"));
+        assert!(!hint(false, "", None).contains("[SCROLL]"));
+        let s = render_hint(&Hint {
+            scrolled: true,
+            copying: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("[COPY]"));
+        assert!(!s.contains("[SCROLL]"));
+        let s = render_hint(&Hint {
+            scrolled: true,
+            search: Some((2, 5)),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("[SEARCH 2/5]"));
+        assert!(!s.contains("[SCROLL]"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_dropped() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            dropped: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\u{26a0} output dropped"));
+        assert!(!hint(false, "", None).contains("dropped"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_spinner_advances() {
+        let glyphs = Glyphs::ascii();
+        let render = |frame| {
+            render_hint(&Hint {
+                at_prompt: Some(false),
+                frame,
+                ..plain_hint(&glyphs)
+            })
+        };
+        assert!(render(0).contains("| busy"));
+        assert!(render(SPINNER_STEP).contains("/ busy"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_cwd() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            cwd: Some("~/work/api"),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("~/work/api"));
+        let long = format!("~/{}/api", "deep/".repeat(30));
+        let s = render_hint(&Hint {
+            cols: 60,
+            cwd: Some(&long),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\u{2026}") && s.contains("api"));
+        assert!(!s.contains(&long));
+    }
+
+    #[test]
+    fn test_render_hint_bar_ai_tool() {
+        let glyphs = Glyphs::default();
+        let mut tool = AiTool {
+            pattern: "aider".into(),
+            name: "Aider".into(),
+            glyph: "\u{2726}".into(),
+        };
+        let s = render_hint(&Hint {
+            ai_tool: Some(&tool),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\u{2726} Aider"));
+        tool.glyph.clear();
+        let s = render_hint(&Hint {
+            ai_tool: Some(&tool),
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\x1b[36mAider"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_theme_colors() {
+        let glyphs = Glyphs::default();
+        let theme = Theme {
+            background: "48;5;17".into(),
+            error: "91".into(),
+            ..Theme::default()
+        };
+        let s = render_hint(&Hint {
+            message: Some("oops"),
+            theme: &theme,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("\x1b[48;5;17m\x1b[91moops"));
+        assert!(!s.contains("48;5;236"));
+    }
+
+    #[test]
+    fn test_render_hint_bar_cwd_missing() {
+        let glyphs = Glyphs::default();
+        let s = render_hint(&Hint {
+            cwd_missing: true,
+            ..plain_hint(&glyphs)
+        });
+        assert!(s.contains("cwd gone"));
+        assert!(!hint(false, "", None).contains("cwd gone"));
+    }
+
+    #[test]
+    fn test_render_note_bar_truncates() {
+        let mut buf = Vec::new();
+        render_note_bar(
+            &mut buf,
+            22,
+            12,
+            "waiting on API review",
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
-        assert!(s.contains("v0.3.0"));
-        assert!(s.contains("npm i -g @steadymoka/murmur"));
+        assert!(s.contains("\x1b[22;1H"));
+        assert!(s.contains("waiting "));
+        assert!(!s.contains("review"));
     }
 
     #[test]
-    fn test_render_bar_area_non_ai() {
+    fn test_render_input_line() {
         let mut buf = Vec::new();
-        render_bar_area(&mut buf, 24, 2, 80, false, "ignored", None);
+        render_input_line(&mut buf, 24, "note", "hello", "", &Theme::default());
         let s = output(&buf);
-        // Should have separator but no pin bar
-        assert!(s.contains("\u{2500}")); // separator
-        assert!(!s.contains("\u{258e}")); // no pin bar char
+        assert!(s.contains("\x1b[24;1H"));
+        assert!(s.contains(" note: "));
+        assert!(s.contains("hello"));
     }
 
     #[test]
-    fn test_render_bar_area_ai() {
+    fn test_render_session_info() {
         let mut buf = Vec::new();
-        render_bar_area(&mut buf, 24, 3, 80, true, "prompt", None);
+        render_session_info(
+            &mut buf,
+            24,
+            Some(4242),
+            "/tmp/project",
+            &Theme::default(),
+            &Glyphs::default(),
+        );
         let s = output(&buf);
-        assert!(s.contains("\u{2500}")); // separator
-        assert!(s.contains("\u{258e}")); // pin bar char
-        assert!(s.contains("prompt"));
+        assert!(s.contains("\x1b[24;1H"));
+        assert!(s.contains("4242"));
+        assert!(s.contains("/tmp/project"));
+    }
+
+    #[test]
+    fn test_render_session_info_without_pid() {
+        let mut buf = Vec::new();
+        render_session_info(
+            &mut buf,
+            24,
+            None,
+            "/tmp",
+            &Theme::default(),
+            &Glyphs::default(),
+        );
+        assert!(output(&buf).contains("pid \x1b[36m?"));
+    }
+
+    #[test]
+    fn test_render_update_message() {
+        let mut buf = Vec::new();
+        render_update_message(&mut buf, 24, "0.3.0", &Theme::default());
+        let s = output(&buf);
+        assert!(s.contains("v0.3.0"));
+        assert!(s.contains("npm i -g @steadymoka/murmur"));
     }
 }
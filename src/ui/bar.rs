@@ -1,7 +1,15 @@
 use std::io::Write;
 
 use super::ansi;
-use crate::layout::{self, BarResize};
+use super::split;
+use super::theme::{Glyphs, Theme};
+use crate::app::copy::CopyMode;
+use crate::app::input::LineInput;
+use crate::app::search::Match;
+use crate::config::BarConfig;
+use crate::key::keymap::Keymap;
+use crate::layout::{self, BarContent, BarResize};
+use crate::session::AiTool;
 
 /// All data needed to render the bar area. Gathered from App + Session
 /// to avoid passing mutable references across boundaries.
@@ -12,29 +20,138 @@ pub struct BarState<'a> {
     pub is_ai: bool,
     pub pinned_prompt: &'a str,
     pub pin_position: Option<(usize, usize)>,
+    /// Live mode: the prompt currently being typed, shown instead of the pin.
+    pub draft: Option<String>,
+    pub note: Option<&'a str>,
+    pub input: Option<&'a LineInput>,
     pub prefix_armed: bool,
     pub window_title: &'a str,
     pub update_version: Option<&'a str>,
+    pub paused: bool,
+    /// Output is being recorded to a file.
+    pub recording: bool,
+    /// Typed input goes to every session.
+    pub broadcast: bool,
+    pub message: Option<&'a str>,
+    pub session_name: &'a str,
+    /// `Some((1-based index, total))` when more than one session is open.
+    pub session_position: Option<(usize, usize)>,
+    /// 1-based positions of other sessions with output not yet seen.
+    pub active_sessions: Vec<usize>,
+    /// Working directory for display, when `bar.show_cwd` is on.
+    pub cwd: Option<String>,
+    pub ai_tool: Option<&'a AiTool>,
+    pub cwd_missing: bool,
+    pub starting: bool,
+    pub exited: Option<u32>,
+    /// How long the program has run, from `session::compact_duration`.
+    pub uptime: Option<String>,
+    /// How long output has been quiet, once that passes a minute.
+    pub quiet: Option<String>,
+    pub scrolled: bool,
+    pub copying: bool,
+    pub search: Option<(usize, usize)>,
+    pub dropped: bool,
+    pub frame: u64,
+    pub at_prompt: Option<bool>,
+    pub bar: &'a BarConfig,
+    pub keymap: &'a Keymap,
+    pub theme: &'a Theme,
+    pub glyphs: &'a Glyphs,
+}
+
+/// Redraw the separator (when enabled) and the pin bar, leaving the hint bar untouched.
+pub fn render_pin_area(w: &mut impl Write, state: &BarState) {
+    let w = &mut ansi::SgrFilter::new(w, state.theme.color);
+    ansi::save_cursor(w);
+    let mut row = state.rows.saturating_sub(state.bar_rows) + 1;
+    if state.bar.separator {
+        ansi::render_separator(w, row, state.cols, state.theme, state.glyphs);
+        row += 1;
+    }
+    if let Some(note) = state.note {
+        ansi::render_note_bar(w, row, state.cols, note, state.theme, state.glyphs);
+        row += 1;
+    }
+    if state.is_ai {
+        // A draft reuses the rows sized for the pinned prompt
+        let pinned_rows =
+            ansi::pin_lines(state.pinned_prompt, state.cols, state.bar.truncate).len();
+        let view = match &state.draft {
+            Some(draft) => ansi::PinView {
+                prompt: draft,
+                position: None,
+                max_rows: pinned_rows.min(state.bar.max_pin_rows),
+                truncate: state.bar.truncate,
+                draft: true,
+            },
+            None => ansi::PinView {
+                prompt: state.pinned_prompt,
+                position: state.pin_position,
+                max_rows: state.bar.max_pin_rows,
+                truncate: state.bar.truncate,
+                draft: false,
+            },
+        };
+        ansi::render_pin_bar(w, row, state.cols, &view, state.theme, state.glyphs);
+    }
+    ansi::restore_cursor(w);
+}
+
+/// Redraw the hint bar on the last row, or the line being edited while prompting for input.
+pub fn render_hint(w: &mut impl Write, state: &BarState) {
+    let w = &mut ansi::SgrFilter::new(w, state.theme.color);
+    if let Some(input) = state.input {
+        let room = (state.cols + 1).saturating_sub(input.cursor_col());
+        let hint = input.hint.as_deref().map_or(String::new(), |hint| {
+            ansi::truncate_end(&format!("  {hint}"), room.into(), &state.glyphs.ellipsis)
+        });
+        ansi::render_input_line(
+            w,
+            state.rows,
+            input.label(),
+            &input.buffer,
+            &hint,
+            state.theme,
+        );
+        return;
+    }
+    let hint = ansi::Hint {
+        cols: state.cols,
+        prefix_armed: state.prefix_armed,
+        window_title: state.window_title,
+        update_version: state.update_version,
+        paused: state.paused,
+        recording: state.recording,
+        broadcast: state.broadcast,
+        message: state.message,
+        session_name: state.session_name,
+        session_position: state.session_position,
+        active_sessions: &state.active_sessions,
+        cwd: state.cwd.as_deref(),
+        ai_tool: state.ai_tool,
+        cwd_missing: state.cwd_missing,
+        starting: state.starting,
+        exited: state.exited,
+        uptime: state.uptime.as_deref(),
+        quiet: state.quiet.as_deref(),
+        scrolled: state.scrolled,
+        copying: state.copying,
+        search: state.search,
+        dropped: state.dropped,
+        frame: state.frame,
+        at_prompt: state.at_prompt,
+        keymap: state.keymap,
+        theme: state.theme,
+        glyphs: state.glyphs,
+    };
+    ansi::render_hint_bar(w, state.rows, &hint);
 }
 
 /// Full bar redraw: separator + pin bar + hint bar.
 pub fn render_bars(w: &mut impl Write, state: &BarState) {
-    ansi::render_bar_area(
-        w,
-        state.rows,
-        state.bar_rows,
-        state.cols,
-        state.is_ai,
-        state.pinned_prompt,
-        state.pin_position,
-    );
-    ansi::render_hint_bar(
-        w,
-        state.rows,
-        state.prefix_armed,
-        state.window_title,
-        state.update_version,
-    );
+    render_pin_area(w, state);
+    render_hint(w, state);
 }
 
 /// Handle bar resize: clear old rows, compute new layout.
@@ -43,43 +160,422 @@ pub fn apply_bar_resize(
     w: &mut impl Write,
     rows: u16,
     old_bar_rows: u16,
-    pinned_prompt: &str,
-    is_ai: bool,
+    content: &BarContent,
+    bar: &BarConfig,
 ) -> Option<BarResize> {
-    let resize = layout::compute_bar_resize(rows, old_bar_rows, pinned_prompt, is_ai)?;
+    let resize = layout::compute_bar_resize(rows, old_bar_rows, content, bar)?;
     ansi::clear_rows(w, resize.clear_from, resize.clear_to);
     Some(resize)
 }
 
+/// Repaint the whole session screen from the parser, then reset SGR so the
+/// bars drawn next don't inherit the attributes of the last restored cell.
+/// The cursor is left where the screen put it.
+pub fn restore_screen(w: &mut impl Write, screen: &vt100::Screen) {
+    w.write_all(&screen.contents_formatted()).ok();
+    write!(w, "{}", ansi::RESET).ok();
+}
+
+/// Put the terminal cursor where `screen` has it. Past the last column the cursor
+/// is waiting to wrap, which a cursor move can't express: the terminal would land
+/// on the last column and overwrite it with the next character instead of wrapping.
+/// So the glyph there is written again, leaving the terminal waiting to wrap too.
+/// vt100 counts columns in cells, so wide characters need nothing else.
+pub fn restore_cursor(w: &mut impl Write, screen: &vt100::Screen) {
+    let (row, col) = screen.cursor_position();
+    let (_, cols) = screen.size();
+    if col < cols {
+        ansi::move_to(w, row + 1, col + 1);
+        return;
+    }
+    let last = cols.saturating_sub(1);
+    let start = match screen.cell(row, last) {
+        Some(cell) if cell.is_wide_continuation() => last.saturating_sub(1),
+        _ => last,
+    };
+    ansi::move_to(w, row + 1, start + 1);
+    if let Some(cell) = screen.cell(row, start) {
+        let text = if cell.has_contents() {
+            cell.contents()
+        } else {
+            " "
+        };
+        write!(w, "{}{text}", split::cell_sgr(cell)).ok();
+    }
+    w.write_all(&screen.attributes_formatted()).ok();
+}
+
+/// Copy mode: repaint the frozen view with the selection in reverse video,
+/// leaving the cursor on the copy cursor.
+pub fn render_copy_view(w: &mut impl Write, copy: &CopyMode) {
+    restore_screen(w, &copy.screen);
+    let (rows, _) = copy.screen.size();
+    for row in 0..rows {
+        if let Some((first, last)) = copy.selected_cols(row) {
+            write_reversed(w, &copy.screen, row, first, last);
+        }
+    }
+    ansi::move_to(w, copy.cursor.row + 1, copy.cursor.col + 1);
+}
+
+/// Search: repaint the view with the current match in reverse video.
+pub fn render_search_view(w: &mut impl Write, screen: &vt100::Screen, row: u16, m: &Match) {
+    restore_screen(w, screen);
+    if m.width > 0 {
+        write_reversed(w, screen, row, m.col, m.col + m.width - 1);
+    }
+}
+
+/// Redraw cells `first..=last` of `row` in reverse video, as plain text.
+fn write_reversed(w: &mut impl Write, screen: &vt100::Screen, row: u16, first: u16, last: u16) {
+    ansi::move_to(w, row + 1, first + 1);
+    write!(w, "{}", ansi::REVERSE).ok();
+    for col in first..=last {
+        match screen.cell(row, col) {
+            Some(cell) if cell.is_wide_continuation() => None,
+            Some(cell) if cell.has_contents() => write!(w, "{}", cell.contents()).ok(),
+            _ => write!(w, " ").ok(),
+        };
+    }
+    write!(w, "{}", ansi::RESET).ok();
+}
+
+/// Overlay mode: repaint the program rows hidden under the bars from the parser's screen,
+/// so the terminal matches the parser again before new output is forwarded.
+/// The caller is responsible for restoring the program's cursor and attributes.
+pub fn clear_overlay(w: &mut impl Write, screen: &vt100::Screen, rows: u16, bar_rows: u16) {
+    let (_, cols) = screen.size();
+    let first = rows.saturating_sub(bar_rows);
+    ansi::clear_rows(w, first + 1, rows);
+    for (i, line) in screen
+        .rows_formatted(0, cols)
+        .enumerate()
+        .skip(first as usize)
+        .take(bar_rows as usize)
+    {
+        ansi::move_to(w, i as u16 + 1, 1);
+        w.write_all(&line).ok();
+        write!(w, "\x1b[0m").ok();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::input::InputKind;
 
-    #[test]
-    fn render_bars_writes_output() {
-        let mut buf = Vec::new();
-        let state = BarState {
-            rows: 24,
+    fn content(pinned_prompt: &str, is_ai_tool: bool) -> BarContent<'_> {
+        BarContent {
+            pinned_prompt,
+            is_ai_tool,
+            has_note: false,
             cols: 80,
-            bar_rows: 3,
-            is_ai: true,
-            pinned_prompt: "test",
-            pin_position: None,
-            prefix_armed: false,
-            window_title: "title",
-            update_version: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Fixture {
+        bar: BarConfig,
+        keymap: Keymap,
+        theme: Theme,
+        glyphs: Glyphs,
+    }
+
+    impl Fixture {
+        fn state(&self, is_ai: bool) -> BarState<'_> {
+            BarState {
+                rows: 24,
+                cols: 80,
+                bar_rows: if is_ai { 3 } else { 2 },
+                is_ai,
+                pinned_prompt: "test",
+                pin_position: None,
+                draft: None,
+                note: None,
+                input: None,
+                prefix_armed: false,
+                window_title: "title",
+                update_version: None,
+                paused: false,
+                recording: false,
+                broadcast: false,
+                message: None,
+                session_name: "proj",
+                session_position: None,
+                active_sessions: Vec::new(),
+                cwd: None,
+                ai_tool: None,
+                cwd_missing: false,
+                starting: false,
+                exited: None,
+                uptime: None,
+                quiet: None,
+                scrolled: false,
+                copying: false,
+                search: None,
+                dropped: false,
+                frame: 0,
+                at_prompt: None,
+                bar: &self.bar,
+                keymap: &self.keymap,
+                theme: &self.theme,
+                glyphs: &self.glyphs,
+            }
+        }
+    }
+
+    /// All SGR parameter lists in `s`.
+    fn sgr_params(s: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut rest = s;
+        while let Some(start) = rest.find("\x1b[") {
+            rest = &rest[start + 2..];
+            let end = rest
+                .find(|c: char| ('\x40'..='\x7e').contains(&c))
+                .unwrap_or(rest.len());
+            if rest[end..].starts_with('m') {
+                found.push(rest[..end].to_string());
+            }
+            rest = &rest[end..];
+        }
+        found
+    }
+
+    fn has_color_sgr(s: &str) -> bool {
+        sgr_params(s).iter().any(|params| {
+            params
+                .split(';')
+                .any(|p| matches!(p.parse::<u16>(), Ok(30..=49 | 58 | 90..=97 | 100..=107)))
+        })
+    }
+
+    #[test]
+    fn render_bars_no_color() {
+        let fixture = Fixture {
+            theme: Theme {
+                color: false,
+                ..Theme::default()
+            },
+            ..Fixture::default()
         };
+        let mut state = fixture.state(true);
+        state.pin_position = Some((1, 2));
+        state.update_version = Some("9.9.9");
+        state.paused = true;
+        state.message = Some("oops");
+        state.note = Some("note");
+        let mut buf = Vec::new();
         render_bars(&mut buf, &state);
+        state.prefix_armed = true;
+        render_bars(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(!has_color_sgr(&s), "{s:?}");
+        assert!(s.contains("\x1b[1m")); // bold kept
+        assert!(s.contains("test")); // layout intact
+        assert!(has_color_sgr(&{
+            let mut buf = Vec::new();
+            render_bars(&mut buf, &Fixture::default().state(true));
+            String::from_utf8_lossy(&buf).to_string()
+        }));
+    }
+
+    #[test]
+    fn render_bars_writes_output() {
+        let mut buf = Vec::new();
+        render_bars(&mut buf, &Fixture::default().state(true));
         assert!(!buf.is_empty());
         let s = String::from_utf8_lossy(&buf);
         assert!(s.contains("test")); // pin bar content
         assert!(s.contains("title")); // hint bar content
     }
 
+    #[test]
+    fn render_pin_area_non_ai() {
+        let mut buf = Vec::new();
+        render_pin_area(&mut buf, &Fixture::default().state(false));
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("\u{2500}")); // separator
+        assert!(!s.contains("\u{258e}")); // no pin bar char
+    }
+
+    #[test]
+    fn render_pin_area_ai() {
+        let mut buf = Vec::new();
+        render_pin_area(&mut buf, &Fixture::default().state(true));
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("\u{2500}")); // separator
+        assert!(s.contains("\x1b[22;1H")); // separator row
+        assert!(s.contains("\x1b[23;1H")); // pin row below it
+        assert!(s.contains("test"));
+    }
+
+    #[test]
+    fn render_pin_area_without_separator() {
+        let mut buf = Vec::new();
+        let fixture = Fixture {
+            bar: BarConfig {
+                separator: false,
+                ..BarConfig::default()
+            },
+            ..Fixture::default()
+        };
+        let mut state = fixture.state(true);
+        state.bar_rows = 2;
+        render_pin_area(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(!s.contains("\u{2500}"));
+        assert!(s.contains("\x1b[23;1H")); // pin bar takes the first bar row
+        assert!(s.contains("test"));
+    }
+
+    #[test]
+    fn render_pin_area_note_above_pin() {
+        let mut buf = Vec::new();
+        let fixture = Fixture::default();
+        let mut state = fixture.state(true);
+        state.bar_rows = 4;
+        state.note = Some("waiting on review");
+        render_pin_area(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        let note = s.find("waiting on review").unwrap();
+        let pin = s.find("\x1b[23;1H").unwrap();
+        assert!(s.contains("\x1b[22;1H"));
+        assert!(note < pin);
+    }
+
+    #[test]
+    fn render_pin_area_draft_uses_pin_rows() {
+        let fixture = Fixture::default();
+        let mut state = fixture.state(true);
+        state.draft = Some("typing\nmore".into());
+        let mut buf = Vec::new();
+        render_pin_area(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("typing"));
+        assert!(!s.contains("\x1b[24;1H")); // hint row untouched
+        assert!(s.contains("(+1 lines)"));
+        assert!(!s.contains("test"));
+    }
+
+    #[test]
+    fn render_hint_shows_input() {
+        let fixture = Fixture::default();
+        let input = LineInput::new(InputKind::Note, "draft");
+        let mut state = fixture.state(false);
+        state.input = Some(&input);
+        let mut buf = Vec::new();
+        render_hint(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("note: "));
+        assert!(s.contains("draft"));
+        assert!(!s.contains("Ctrl+\\"));
+    }
+
+    #[test]
+    fn render_hint_fits_the_input_hint_in_the_row() {
+        let fixture = Fixture::default();
+        let mut input = LineInput::new(InputKind::NewSession, "ap");
+        input.hint = Some("api  app  apps".into());
+        let mut state = fixture.state(false);
+        state.cols = 20;
+        state.input = Some(&input);
+        let mut buf = Vec::new();
+        render_hint(&mut buf, &state);
+        let s = String::from_utf8_lossy(&buf);
+        // " new: ap" leaves 12 columns
+        assert!(s.contains("  api  app \u{2026}"));
+    }
+
+    #[test]
+    fn render_bars_ascii_has_no_multibyte_glyphs() {
+        let fixture = Fixture {
+            glyphs: Glyphs::ascii(),
+            ..Fixture::default()
+        };
+        let mut state = fixture.state(true);
+        state.pin_position = Some((1, 2));
+        state.update_version = Some("9.9.9");
+        state.paused = true;
+        state.message = Some("not a directory: x");
+        state.session_position = Some((1, 2));
+        state.cwd_missing = true;
+        state.starting = true;
+        state.exited = Some(1);
+        state.scrolled = true;
+        state.dropped = true;
+        state.note = Some("note");
+        let mut buf = Vec::new();
+        render_bars(&mut buf, &state);
+        state.prefix_armed = true;
+        render_bars(&mut buf, &state);
+        assert!(buf.is_ascii(), "{}", String::from_utf8_lossy(&buf));
+    }
+
+    #[test]
+    fn clear_overlay_repaints_hidden_rows() {
+        let mut parser = vt100::Parser::new(5, 20, 0);
+        parser.process(b"row1\r\nrow2\r\nrow3\r\nrow4\r\nrow5");
+        let mut buf = Vec::new();
+        clear_overlay(&mut buf, parser.screen(), 5, 2);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("\x1b[4;1H"));
+        assert!(s.contains("row4"));
+        assert!(s.contains("row5"));
+        assert!(!s.contains("row3"));
+    }
+
+    #[test]
+    fn restore_screen_resets_before_bars() {
+        let mut parser = vt100::Parser::new(24, 80, 0);
+        parser.process(b"plain \x1b[41;37mred");
+        let mut buf = Vec::new();
+        restore_screen(&mut buf, parser.screen());
+        let restored = buf.len();
+        render_pin_area(&mut buf, &Fixture::default().state(true));
+        let s = String::from_utf8_lossy(&buf);
+        let red = s.rfind("41").unwrap();
+        let reset = s[red..].find("\x1b[0m").unwrap() + red;
+        assert!(reset < restored);
+        assert!(reset < s.find("\x1b[2K").unwrap());
+
+        // the bars still carry their own colors
+        assert!(has_color_sgr(&s[restored..]));
+        assert!(s[restored..].contains("test"));
+    }
+
+    #[test]
+    fn render_copy_view_reverses_selection() {
+        let mut parser = vt100::Parser::new(5, 20, 0);
+        parser.process(b"hello world");
+        let mut copy = CopyMode::new(parser.screen());
+        copy.anchor = Some(crate::app::copy::Pos { row: 0, col: 6 });
+        copy.cursor = crate::app::copy::Pos { row: 0, col: 10 };
+        let mut buf = Vec::new();
+        render_copy_view(&mut buf, &copy);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.contains("\x1b[1;7H\x1b[7mworld\x1b[0m"));
+        assert!(s.ends_with("\x1b[1;11H"));
+    }
+
+    #[test]
+    fn render_search_view_reverses_match() {
+        let mut parser = vt100::Parser::new(5, 20, 0);
+        parser.process(b"one\r\nfind me here");
+        let m = Match {
+            line: 1,
+            col: 5,
+            width: 2,
+        };
+        let mut buf = Vec::new();
+        render_search_view(&mut buf, parser.screen(), 1, &m);
+        let s = String::from_utf8_lossy(&buf);
+        assert!(s.ends_with("\x1b[2;6H\x1b[7mme\x1b[0m"));
+    }
+
     #[test]
     fn apply_bar_resize_no_change() {
         let mut buf = Vec::new();
-        let result = apply_bar_resize(&mut buf, 24, 2, "", false);
+        let result = apply_bar_resize(&mut buf, 24, 2, &content("", false), &BarConfig::default());
         assert!(result.is_none());
         assert!(buf.is_empty());
     }
@@ -87,10 +583,40 @@ mod tests {
     #[test]
     fn apply_bar_resize_clears_rows() {
         let mut buf = Vec::new();
-        let result = apply_bar_resize(&mut buf, 24, 2, "prompt", true);
+        let result = apply_bar_resize(
+            &mut buf,
+            24,
+            2,
+            &content("prompt", true),
+            &BarConfig::default(),
+        );
         assert!(result.is_some());
         let resize = result.unwrap();
         assert_eq!(resize.new_bar_rows, 3);
         assert!(!buf.is_empty()); // clear_rows wrote something
     }
+
+    fn cursor_after(cols: u16, input: &str) -> String {
+        let mut parser = vt100::Parser::new(3, cols, 0);
+        parser.process(input.as_bytes());
+        let mut buf = Vec::new();
+        restore_cursor(&mut buf, parser.screen());
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn restore_cursor_counts_wide_cells() {
+        // `a`, a two-cell glyph, `b`: the cursor sits in the fifth cell
+        assert_eq!(cursor_after(10, "a\u{4F60}b"), "\x1b[1;5H");
+        assert_eq!(cursor_after(10, "a\u{4F60}b\x1b[2D"), "\x1b[1;3H");
+        // No room for a wide glyph in the last cell, so it wrapped
+        assert_eq!(cursor_after(10, "123456789\u{4F60}"), "\x1b[2;3H");
+    }
+
+    #[test]
+    fn restore_cursor_keeps_a_pending_wrap() {
+        assert_eq!(cursor_after(10, "1234567890"), "\x1b[1;10H\x1b[0m0\x1b[m");
+        let wide = cursor_after(10, "\u{4F60}\u{4F60}\u{4F60}\u{4F60}\x1b[31m\u{4F60}");
+        assert!(wide.starts_with("\x1b[1;9H\x1b[0;31m\u{4F60}"));
+    }
 }
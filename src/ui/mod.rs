@@ -1,2 +1,4 @@
 pub mod ansi;
 pub mod bar;
+pub mod split;
+pub mod theme;
@@ -0,0 +1,126 @@
+use std::io::Write;
+
+use super::ansi;
+use super::theme::{self, Glyphs, Theme};
+
+/// Widths of the left and right panes on a `cols`-wide terminal, leaving one
+/// column between them for the divider.
+pub fn pane_widths(cols: u16) -> (u16, u16) {
+    let usable = cols.saturating_sub(1);
+    let left = usable.div_ceil(2).max(1);
+    (left, usable.saturating_sub(left).max(1))
+}
+
+/// Draw `screen` as a pane whose top-left cell is `col` (1-indexed) on the
+/// first row. Every cell is written, so the pane needs no clearing first.
+pub fn render_pane(w: &mut impl Write, screen: &vt100::Screen, col: u16) {
+    let (rows, cols) = screen.size();
+    for row in 0..rows {
+        ansi::move_to(w, row + 1, col);
+        let mut style = String::new();
+        for c in 0..cols {
+            let Some(cell) = screen.cell(row, c) else {
+                break;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+            let next = cell_sgr(cell);
+            if next != style {
+                w.write_all(next.as_bytes()).ok();
+                style = next;
+            }
+            if cell.has_contents() {
+                w.write_all(cell.contents().as_bytes()).ok();
+            } else {
+                w.write_all(b" ").ok();
+            }
+        }
+        write!(w, "{}", ansi::RESET).ok();
+    }
+}
+
+/// Draw the divider down column `col` for `rows` rows, in the separator color.
+pub fn render_divider(w: &mut impl Write, col: u16, rows: u16, theme: &Theme, glyphs: &Glyphs) {
+    let color = theme::sgr(&theme.separator);
+    for row in 1..=rows {
+        ansi::move_to(w, row, col);
+        write!(w, "{color}{}{}", glyphs.divider, ansi::RESET).ok();
+    }
+}
+
+/// Full SGR sequence for a cell's attributes, starting from a reset.
+pub fn cell_sgr(cell: &vt100::Cell) -> String {
+    let mut params = vec!["0".to_string()];
+    for (on, code) in [
+        (cell.bold(), "1"),
+        (cell.dim(), "2"),
+        (cell.italic(), "3"),
+        (cell.underline(), "4"),
+        (cell.inverse(), "7"),
+    ] {
+        if on {
+            params.push(code.to_string());
+        }
+    }
+    params.extend(color(cell.fgcolor(), 30, 90, 38));
+    params.extend(color(cell.bgcolor(), 40, 100, 48));
+    format!("\x1b[{}m", params.join(";"))
+}
+
+fn color(color: vt100::Color, base: u8, bright: u8, extended: u8) -> Option<String> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(n @ 0..=7) => Some((base + n).to_string()),
+        vt100::Color::Idx(n @ 8..=15) => Some((bright + n - 8).to_string()),
+        vt100::Color::Idx(n) => Some(format!("{extended};5;{n}")),
+        vt100::Color::Rgb(r, g, b) => Some(format!("{extended};2;{r};{g};{b}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(rows: u16, cols: u16, input: &[u8]) -> vt100::Parser {
+        let mut parser = vt100::Parser::new(rows, cols, 0);
+        parser.process(input);
+        parser
+    }
+
+    #[test]
+    fn pane_widths_leave_a_divider() {
+        assert_eq!(pane_widths(81), (40, 40));
+        assert_eq!(pane_widths(80), (40, 39));
+        assert_eq!(pane_widths(2), (1, 1));
+    }
+
+    #[test]
+    fn render_pane_writes_every_cell_at_the_offset() {
+        let parser = screen(2, 4, b"ab");
+        let mut buf = Vec::new();
+        render_pane(&mut buf, parser.screen(), 42);
+        let s = String::from_utf8(buf).unwrap();
+        assert!(s.starts_with("\x1b[1;42H\x1b[0mab  "));
+        assert!(s.contains("\x1b[2;42H\x1b[0m    "));
+    }
+
+    #[test]
+    fn render_pane_keeps_colors() {
+        let parser = screen(1, 6, b"\x1b[1;31mred\x1b[0m\x1b[38;5;200mx");
+        let mut buf = Vec::new();
+        render_pane(&mut buf, parser.screen(), 1);
+        let s = String::from_utf8(buf).unwrap();
+        assert!(s.contains("\x1b[0;1;31mred"));
+        assert!(s.contains("\x1b[0;38;5;200mx"));
+    }
+
+    #[test]
+    fn render_pane_skips_wide_continuations() {
+        let parser = screen(1, 4, "\u{4F60}!".as_bytes());
+        let mut buf = Vec::new();
+        render_pane(&mut buf, parser.screen(), 1);
+        let s = String::from_utf8(buf).unwrap();
+        assert!(s.contains("\u{4F60}! "));
+    }
+}
@@ -0,0 +1,166 @@
+use serde::Deserialize;
+
+/// Bar colors, each given as SGR parameters (e.g. `"90"`, `"38;5;240"`).
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub separator: String,
+    /// Background of the pin, note and hint bars.
+    pub background: String,
+    /// Secondary text: dividers, the draft prompt, idle state.
+    pub muted: String,
+    /// Session position, the prefix key and other labels.
+    pub accent: String,
+    pub pin_marker: String,
+    pub pin_text: String,
+    pub note_marker: String,
+    /// The AI tool's name in the hint bar.
+    pub ai_accent: String,
+    /// Busy, paused and other states that need attention.
+    pub warning: String,
+    pub error: String,
+    pub update: String,
+    /// The ` Ctrl+\ ` label while the prefix is armed.
+    pub prefix: String,
+    /// The key list while the prefix is armed.
+    pub prefix_keys: String,
+    /// `false` (or `--no-color` / `NO_COLOR`) keeps only attributes like bold.
+    pub color: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            separator: "90".into(),
+            background: "48;5;236".into(),
+            muted: "90".into(),
+            accent: "36".into(),
+            pin_marker: "36".into(),
+            pin_text: "33".into(),
+            note_marker: "32".into(),
+            ai_accent: "36".into(),
+            warning: "33".into(),
+            error: "31".into(),
+            update: "32".into(),
+            prefix: "1;30;46".into(),
+            prefix_keys: "0;36".into(),
+            color: true,
+        }
+    }
+}
+
+/// Decorative characters drawn in the bars. Fonts without these glyphs can
+/// switch to [`Glyphs::ascii`] via `--ascii`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Glyphs {
+    /// Left edge of each pin bar line.
+    pub marker: String,
+    /// Divider between hint bar segments.
+    pub divider: String,
+    /// Repeated to draw the separator rule.
+    pub rule: String,
+    pub arrow: String,
+    pub update: String,
+    pub paused: String,
+    /// Before "REC" while a session is being recorded.
+    pub recording: String,
+    pub warning: String,
+    /// Marks where a line that doesn't fit was cut.
+    pub ellipsis: String,
+    /// Frames of the busy spinner, one character each.
+    pub spinner: String,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            marker: "\u{258e}".into(),
+            divider: "\u{2502}".into(),
+            rule: "\u{2500}".into(),
+            arrow: "\u{2192}".into(),
+            update: "\u{2191}".into(),
+            paused: "\u{23f8}".into(),
+            recording: "\u{25cf}".into(),
+            warning: "\u{26a0}".into(),
+            ellipsis: "\u{2026}".into(),
+            spinner:
+                "\u{280b}\u{2819}\u{2839}\u{2838}\u{283c}\u{2834}\u{2826}\u{2827}\u{2807}\u{280f}"
+                    .into(),
+        }
+    }
+}
+
+impl Glyphs {
+    pub fn ascii() -> Self {
+        Self {
+            marker: "|".into(),
+            divider: "|".into(),
+            rule: "-".into(),
+            arrow: ">".into(),
+            update: "^".into(),
+            paused: "||".into(),
+            recording: "*".into(),
+            warning: "!".into(),
+            ellipsis: "...".into(),
+            spinner: "|/-\\".into(),
+        }
+    }
+
+    /// Spinner character for an animation phase.
+    pub fn spinner_frame(&self, phase: u64) -> char {
+        let frames: Vec<char> = self.spinner.chars().collect();
+        if frames.is_empty() {
+            return ' ';
+        }
+        frames[(phase % frames.len() as u64) as usize]
+    }
+}
+
+/// Build the escape sequence for a set of SGR parameters.
+pub fn sgr(params: &str) -> String {
+    format!("\x1b[{params}m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_glyphs_are_ascii() {
+        let g = Glyphs::ascii();
+        for glyph in [
+            g.marker,
+            g.divider,
+            g.rule,
+            g.arrow,
+            g.update,
+            g.paused,
+            g.recording,
+            g.warning,
+            g.ellipsis,
+            g.spinner,
+        ] {
+            assert!(glyph.is_ascii(), "{glyph}");
+        }
+    }
+
+    #[test]
+    fn spinner_frame_cycles() {
+        let g = Glyphs::ascii();
+        assert_eq!(g.spinner_frame(0), '|');
+        assert_eq!(g.spinner_frame(3), '\\');
+        assert_eq!(g.spinner_frame(4), '|');
+        let empty = Glyphs {
+            spinner: String::new(),
+            ..Glyphs::ascii()
+        };
+        assert_eq!(empty.spinner_frame(7), ' ');
+    }
+
+    #[test]
+    fn sgr_wraps_params() {
+        assert_eq!(sgr("90"), "\x1b[90m");
+        assert_eq!(sgr("38;5;240"), "\x1b[38;5;240m");
+    }
+}